@@ -0,0 +1,120 @@
+use crate::parse::{load_optional, parse_lines, ParseError};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Known answers for one input, read from a file such as `data/day3.answers`:
+///
+/// ```text
+/// part1: 109785
+/// part2: 504
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub struct Answers {
+  pub part1: Option<String>,
+  pub part2: Option<String>,
+}
+
+impl Answers {
+  /// The answers file that goes with `input`: same name, `.answers` extension.
+  pub fn path_for(input: &Path) -> PathBuf {
+    input.with_extension("answers")
+  }
+
+  /// Reads `path`, or returns `None` if there is no such file.
+  pub fn load(path: &Path) -> Result<Option<Answers>, String> {
+    load_optional(path, Answers::parse)
+  }
+
+  pub fn parse(contents: &str) -> Result<Answers, ParseError> {
+    let mut answers = Answers::default();
+    let lines = parse_lines(contents, |l| {
+      if l.trim().is_empty() || l.starts_with('#') {
+        return Ok(None);
+      }
+      let (key, value) = l
+        .split_once(':')
+        .ok_or_else(|| ParseError::new("expected 'part1: ANSWER' or 'part2: ANSWER'"))?;
+      match key.trim() {
+        "part1" => Ok(Some((1, value.trim().to_string()))),
+        "part2" => Ok(Some((2, value.trim().to_string()))),
+        other => Err(ParseError::new(format!("unknown part '{}'", other))),
+      }
+    })?;
+    for (part, value) in lines.into_iter().flatten() {
+      match part {
+        1 => answers.part1 = Some(value),
+        _ => answers.part2 = Some(value),
+      }
+    }
+    Ok(answers)
+  }
+
+  pub fn expected(&self, part: u8) -> Option<&str> {
+    match part {
+      1 => self.part1.as_deref(),
+      2 => self.part2.as_deref(),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Verdict {
+  Pass,
+  Fail {
+    expected: String,
+  },
+  /// There is no known answer to check against.
+  Unchecked,
+}
+
+impl Verdict {
+  pub fn check(expected: Option<&str>, actual: Option<&str>) -> Verdict {
+    match expected {
+      None => Verdict::Unchecked,
+      Some(expected) if Some(expected) == actual => Verdict::Pass,
+      Some(expected) => Verdict::Fail {
+        expected: expected.to_string(),
+      },
+    }
+  }
+}
+
+impl fmt::Display for Verdict {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Verdict::Pass => write!(f, "ok"),
+      Verdict::Fail { expected } => write!(f, "FAIL (expected {})", expected),
+      Verdict::Unchecked => write!(f, "unchecked"),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse() {
+    let answers = Answers::parse("part1: 109785\n\npart2: 504\n").unwrap();
+    assert_eq!(answers.expected(1), Some("109785"));
+    assert_eq!(answers.expected(2), Some("504"));
+    assert_eq!(Answers::parse("part3: 1").unwrap_err().line, 1);
+  }
+
+  #[test]
+  fn check() {
+    assert_eq!(Verdict::check(Some("848"), Some("848")), Verdict::Pass);
+    assert_eq!(
+      Verdict::check(Some("848"), Some("847")),
+      Verdict::Fail {
+        expected: "848".to_string()
+      }
+    );
+    assert_eq!(
+      Verdict::check(Some("848"), None).to_string(),
+      "FAIL (expected 848)"
+    );
+    assert_eq!(Verdict::check(None, Some("1")), Verdict::Unchecked);
+  }
+}
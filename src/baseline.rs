@@ -0,0 +1,160 @@
+use crate::bench::{DayBench, Stats};
+use crate::parse::{load_optional, parse_lines, ParseError};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+pub const DEFAULT_PATH: &str = "aoc-baseline.tsv";
+
+const HEADER: &str = "# day\tphase\tsamples\tmin_ns\tmedian_ns\tmean_ns\tstddev_ns";
+
+/// Saved benchmark results, keyed by day and phase ("parse", "part1", ...).
+#[derive(Debug, Default, PartialEq)]
+pub struct Baseline {
+  entries: BTreeMap<(u8, String), Stats>,
+}
+
+/// How one phase's median compares with its baseline.
+#[derive(Debug, PartialEq)]
+pub struct Change {
+  pub baseline: Duration,
+  pub percent: f64,
+  pub regressed: bool,
+}
+
+impl Baseline {
+  /// Reads `path`, treating a missing file as an empty baseline.
+  pub fn load(path: &Path) -> Result<Baseline, String> {
+    Ok(load_optional(path, Baseline::parse)?.unwrap_or_default())
+  }
+
+  pub fn parse(contents: &str) -> Result<Baseline, ParseError> {
+    let rows = parse_lines(contents, |l| {
+      if l.starts_with('#') || l.trim().is_empty() {
+        return Ok(None);
+      }
+      match l.split('\t').collect::<Vec<&str>>()[..] {
+        [day, phase, samples, min, median, mean, stddev] => {
+          let num = |s: &str| {
+            s.parse::<u64>()
+              .map_err(|_| ParseError::new(format!("expected a number, found '{}'", s)))
+          };
+          let nanos = |s: &str| num(s).map(Duration::from_nanos);
+          let stats = Stats {
+            samples: num(samples)? as usize,
+            min: nanos(min)?,
+            median: nanos(median)?,
+            mean: nanos(mean)?,
+            stddev: nanos(stddev)?,
+          };
+          Ok(Some(((num(day)? as u8, phase.to_string()), stats)))
+        }
+        _ => Err(ParseError::new("expected 7 tab-separated fields")),
+      }
+    })?;
+    Ok(Baseline {
+      entries: rows.into_iter().flatten().collect(),
+    })
+  }
+
+  pub fn save(&self, path: &Path) -> Result<(), String> {
+    let mut out = String::new();
+    writeln!(out, "{}", HEADER).unwrap();
+    for ((day, phase), s) in &self.entries {
+      writeln!(
+        out,
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        day,
+        phase,
+        s.samples,
+        s.min.as_nanos(),
+        s.median.as_nanos(),
+        s.mean.as_nanos(),
+        s.stddev.as_nanos()
+      )
+      .unwrap();
+    }
+    fs::write(path, out).map_err(|e| format!("could not write {}: {}", path.display(), e))
+  }
+
+  /// Replaces the saved results for every phase measured in `bench`.
+  pub fn record(&mut self, bench: &DayBench) {
+    for (phase, stats) in bench.phases() {
+      self.entries.insert((bench.day, phase.to_string()), stats);
+    }
+  }
+
+  /// Compares `current` with the saved median for `day`/`phase`. A phase
+  /// regressed when its median grew by more than `threshold` percent.
+  pub fn compare(&self, day: u8, phase: &str, current: &Stats, threshold: f64) -> Option<Change> {
+    let saved = self.entries.get(&(day, phase.to_string()))?;
+    let base = saved.median.as_nanos() as f64;
+    let percent = if base > 0.0 {
+      (current.median.as_nanos() as f64 - base) / base * 100.0
+    } else {
+      0.0
+    };
+    Some(Change {
+      baseline: saved.median,
+      percent,
+      regressed: percent > threshold,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stats(median_ms: u64) -> Stats {
+    let d = Duration::from_millis(median_ms);
+    Stats {
+      samples: 10,
+      min: d,
+      median: d,
+      mean: d,
+      stddev: Duration::default(),
+    }
+  }
+
+  fn bench(day: u8, parse_ms: u64, part1_ms: u64) -> DayBench {
+    DayBench {
+      day,
+      parse: stats(parse_ms),
+      part1: Some(stats(part1_ms)),
+      part2: None,
+    }
+  }
+
+  #[test]
+  fn round_trip() {
+    let mut baseline = Baseline::default();
+    baseline.record(&bench(3, 1, 40));
+    baseline.record(&bench(6, 2, 9));
+    let path = std::env::temp_dir().join(format!("aoc-baseline-{}.tsv", std::process::id()));
+    baseline.save(&path).unwrap();
+    let loaded = Baseline::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, baseline);
+  }
+
+  #[test]
+  fn flags_regressions() {
+    let mut baseline = Baseline::default();
+    baseline.record(&bench(3, 10, 40));
+    let slower = baseline.compare(3, "part1", &stats(50), 10.0).unwrap();
+    assert_eq!(slower.percent, 25.0);
+    assert!(slower.regressed);
+    let noise = baseline.compare(3, "parse", &stats(11), 10.0).unwrap();
+    assert!(!noise.regressed);
+    assert_eq!(baseline.compare(3, "part2", &stats(1), 10.0), None);
+  }
+
+  #[test]
+  fn bad_row() {
+    let err = Baseline::parse("# header\n3\tparse\t10\n").unwrap_err();
+    assert_eq!(err.line, 2);
+  }
+}
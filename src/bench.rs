@@ -0,0 +1,169 @@
+use crate::baseline::Change;
+use crate::parse::ParseError;
+use crate::solution::Entry;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Runs `f` once and returns its result along with how long it took.
+pub fn time<T, F: FnOnce() -> T>(f: F) -> (T, Duration) {
+  let start = Instant::now();
+  let result = f();
+  (result, start.elapsed())
+}
+
+/// Summary of repeated timings of the same piece of work.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+  pub samples: usize,
+  pub min: Duration,
+  pub median: Duration,
+  pub mean: Duration,
+  pub stddev: Duration,
+}
+
+impl Stats {
+  pub fn new(samples: &mut [Duration]) -> Stats {
+    assert!(!samples.is_empty(), "no samples to summarize");
+    samples.sort();
+    let n = samples.len();
+    let median = if n.is_multiple_of(2) {
+      (samples[n / 2 - 1] + samples[n / 2]) / 2
+    } else {
+      samples[n / 2]
+    };
+    let nanos: Vec<f64> = samples.iter().map(|d| d.as_nanos() as f64).collect();
+    let mean = nanos.iter().sum::<f64>() / n as f64;
+    let variance = if n > 1 {
+      nanos.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+    } else {
+      0.0
+    };
+    Stats {
+      samples: n,
+      min: samples[0],
+      median,
+      mean: Duration::from_nanos(mean.round() as u64),
+      stddev: Duration::from_nanos(variance.sqrt().round() as u64),
+    }
+  }
+}
+
+/// Times `f` `iterations` times after one untimed warm-up call. Whatever
+/// `f` returns is dropped outside the timed region.
+pub fn measure<T, F: FnMut() -> T>(iterations: usize, mut f: F) -> Stats {
+  black_box(f());
+  let mut samples = Vec::with_capacity(iterations);
+  for _ in 0..iterations {
+    let (result, elapsed) = time(&mut f);
+    samples.push(elapsed);
+    drop(black_box(result));
+  }
+  Stats::new(&mut samples)
+}
+
+pub struct DayBench {
+  pub day: u8,
+  pub parse: Stats,
+  pub part1: Option<Stats>,
+  pub part2: Option<Stats>,
+}
+
+impl DayBench {
+  /// `(label, stats)` for each phase that was measured.
+  pub fn phases(&self) -> Vec<(&'static str, Stats)> {
+    let mut phases = vec![("parse", self.parse)];
+    phases.extend(self.part1.map(|s| ("part1", s)));
+    phases.extend(self.part2.map(|s| ("part2", s)));
+    phases
+  }
+}
+
+/// Benchmarks parsing and each requested part of one day separately; the
+/// parts reuse a single parsed input.
+pub fn bench_day(
+  entry: &Entry,
+  contents: &str,
+  parts: (bool, bool),
+  iterations: usize,
+) -> Result<DayBench, ParseError> {
+  let solution = &entry.solution;
+  let input = solution.parse(contents)?;
+  let parse = measure(iterations, || solution.parse(contents));
+  let part1 = if parts.0 {
+    Some(measure(iterations, || solution.part1(&*input)))
+  } else {
+    None
+  };
+  let part2 = if parts.1 {
+    Some(measure(iterations, || solution.part2(&*input)))
+  } else {
+    None
+  };
+  Ok(DayBench {
+    day: entry.day,
+    parse,
+    part1,
+    part2,
+  })
+}
+
+pub fn print_header() {
+  println!(
+    "{:<4} {:<6} {:>7} {:>11} {:>11} {:>11} {:>11}  vs baseline",
+    "day", "phase", "samples", "min", "median", "mean", "stddev"
+  );
+}
+
+pub fn print_row(day: u8, phase: &str, stats: &Stats, change: Option<&Change>) {
+  let change = match change {
+    Some(c) if c.regressed => format!("{:+.1}% REGRESSED (was {:.2?})", c.percent, c.baseline),
+    Some(c) => format!("{:+.1}%", c.percent),
+    None => "-".to_string(),
+  };
+  println!(
+    "{:<4} {:<6} {:>7} {:>11} {:>11} {:>11} {:>11}  {}",
+    day,
+    phase,
+    stats.samples,
+    format!("{:.2?}", stats.min),
+    format!("{:.2?}", stats.median),
+    format!("{:.2?}", stats.mean),
+    format!("{:.2?}", stats.stddev),
+    change
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn ms(v: &[u64]) -> Vec<Duration> {
+    v.iter().map(|&m| Duration::from_millis(m)).collect()
+  }
+
+  #[test]
+  fn stats() {
+    let stats = Stats::new(&mut ms(&[4, 2, 9, 5]));
+    assert_eq!(stats.samples, 4);
+    assert_eq!(stats.min, Duration::from_millis(2));
+    assert_eq!(stats.median, Duration::from_micros(4500));
+    assert_eq!(stats.mean, Duration::from_nanos(5_000_000));
+    // sample variance: (1 + 9 + 16 + 0) / 3
+    assert_eq!(stats.stddev.as_micros(), 2943);
+  }
+
+  #[test]
+  fn single_sample() {
+    let stats = Stats::new(&mut ms(&[7]));
+    assert_eq!(stats.median, Duration::from_millis(7));
+    assert_eq!(stats.stddev, Duration::from_millis(0));
+  }
+
+  #[test]
+  fn measure_counts_iterations() {
+    let mut calls = 0;
+    let stats = measure(5, || calls += 1);
+    assert_eq!(stats.samples, 5);
+    assert_eq!(calls, 6);
+  }
+}
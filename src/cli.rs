@@ -0,0 +1,507 @@
+use crate::baseline;
+use crate::config::{self, Config};
+use crate::input::Source;
+use crate::output::Format;
+use crate::pool;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+usage: aoc [COMMAND]
+
+commands:
+  run [DAY] [OPTIONS]   run one day (or all days when DAY is omitted)
+  bench [DAY] [OPTIONS] time parsing and each part separately over many runs
+  verify [DAY] [OPTIONS]
+                        check answers against the input's .answers file
+  batch DAY DIR [OPTIONS]
+                        run DAY against every input file in DIR and check each
+                        against its .answers file, if there is one
+  list                  list the available days
+  new-day N             start day N: src/dayN.rs, an empty data/dayN.txt, and
+                        its entries in src/lib.rs and src/solution.rs
+  help                  show this message
+
+run, bench, verify and batch options:
+  -p, --part N          only run part 1 or 2
+  -i, --input PATH      read the input from PATH instead of the data directory
+                        (`-` reads stdin); not batch
+  --data-dir DIR        look for dayN.txt in DIR (default: $AOC_DATA_DIR, ./data,
+                        then the crate's data directory)
+  --format FORMAT       text (default), json (one object per line) or csv;
+                        not verify
+  --report-normalization
+                        note on stderr when an input needed cleaning up (byte
+                        order mark, CRLF, trailing whitespace or blank lines)
+  --config PATH         read puzzle parameters from PATH (default: aoc.toml, if
+                        it exists), e.g. [day7] workers = 2
+  --set dayN.KEY=VALUE  override one parameter; may be repeated
+
+run options:
+  -j, --jobs N          run up to N days at once (0 = one per CPU, default 1);
+                        answers are still printed in day order
+  --profile             print a tree of where the time went: each day's parse
+                        and parts, and any sections the solutions mark
+  --trace PATH          write the same spans as a Chrome trace (JSON) for
+                        chrome://tracing or ui.perfetto.dev
+  --timeout SECS        give up on a day that takes longer than SECS and
+                        report it as timed out; the other days still run
+
+verify options:
+  --answers PATH        expected answers (default: the input path with an
+                        .answers extension, e.g. data/day3.answers)
+
+bench options:
+  -n, --iterations N    timed runs per phase (default 10, after one warm-up)
+  --baseline PATH       baseline to compare against (default aoc-baseline.tsv)
+  --save-baseline       record this run's timings in the baseline
+  --threshold PCT       flag phases whose median grew by more than PCT percent
+                        (default 10)
+
+With no command, every day is run against its input in the data directory.";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+  Run(RunArgs),
+  Bench(BenchArgs),
+  Verify(VerifyArgs),
+  Batch(BatchArgs),
+  NewDay(u8),
+  List,
+  Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunArgs {
+  pub day: Option<u8>,
+  pub part: Option<u8>,
+  pub input: Option<Source>,
+  pub data_dir: Option<PathBuf>,
+  pub format: Format,
+  pub report_normalization: bool,
+  pub config: Option<PathBuf>,
+  /// `dayN.key=value` overrides, applied after the config file.
+  pub settings: Vec<String>,
+  /// Days to run concurrently; only `run` sets this and the two below.
+  pub jobs: usize,
+  pub profile: bool,
+  pub trace: Option<PathBuf>,
+  pub timeout: Option<Duration>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BenchArgs {
+  pub run: RunArgs,
+  pub iterations: usize,
+  pub baseline: PathBuf,
+  pub save_baseline: bool,
+  pub threshold: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VerifyArgs {
+  pub run: RunArgs,
+  pub answers: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BatchArgs {
+  /// `run.day` is always set.
+  pub run: RunArgs,
+  pub dir: PathBuf,
+}
+
+impl Default for RunArgs {
+  fn default() -> RunArgs {
+    RunArgs {
+      day: None,
+      part: None,
+      input: None,
+      data_dir: None,
+      format: Format::Text,
+      report_normalization: false,
+      config: None,
+      settings: vec![],
+      jobs: 1,
+      profile: false,
+      trace: None,
+      timeout: None,
+    }
+  }
+}
+
+impl RunArgs {
+  pub fn runs_part(&self, part: u8) -> bool {
+    self.part.is_none_or(|p| p == part)
+  }
+
+  /// The config file, then the `--set` overrides. Without `--config`, a
+  /// missing `aoc.toml` just means every day keeps its defaults.
+  pub fn load_config(&self) -> Result<Config, String> {
+    let mut config = match &self.config {
+      Some(path) => Config::load(path, true)?,
+      None => Config::load(config::DEFAULT_PATH.as_ref(), false)?,
+    };
+    for setting in &self.settings {
+      config.set_arg(setting)?;
+    }
+    Ok(config)
+  }
+}
+
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+  let mut args = args.into_iter();
+  match args.next().as_deref() {
+    None => Ok(Command::Run(RunArgs::default())),
+    Some("run") => {
+      let (mut jobs, mut profile, mut trace, mut timeout) = (1, false, None, None);
+      let mut run = parse_run(&mut args, |flag, args| {
+        match flag {
+          "--jobs" | "-j" => {
+            jobs = match parse_number(flag, args.next())? {
+              0 => pool::available(),
+              n => n,
+            };
+          }
+          "--profile" => profile = true,
+          "--trace" => match args.next() {
+            Some(path) => trace = Some(PathBuf::from(path)),
+            None => return Err("--trace needs a path".to_string()),
+          },
+          "--timeout" => {
+            let secs: f64 = parse_number(flag, args.next())?;
+            let limit = Duration::try_from_secs_f64(secs)
+              .ok()
+              .filter(|_| secs > 0.0)
+              .ok_or("--timeout must be a positive number of seconds")?;
+            timeout = Some(limit);
+          }
+          _ => return Ok(false),
+        }
+        Ok(true)
+      })?;
+      run.jobs = jobs;
+      run.profile = profile;
+      run.trace = trace;
+      run.timeout = timeout;
+      Ok(Command::Run(run))
+    }
+    Some("bench") => {
+      let mut bench = BenchArgs {
+        run: RunArgs::default(),
+        iterations: 10,
+        baseline: PathBuf::from(baseline::DEFAULT_PATH),
+        save_baseline: false,
+        threshold: 10.0,
+      };
+      bench.run = parse_run(&mut args, |flag, args| {
+        match flag {
+          "--iterations" | "-n" => {
+            bench.iterations = parse_number(flag, args.next())?;
+            if bench.iterations == 0 {
+              return Err("--iterations must be at least 1".to_string());
+            }
+          }
+          "--baseline" => match args.next() {
+            Some(path) => bench.baseline = PathBuf::from(path),
+            None => return Err("--baseline needs a path".to_string()),
+          },
+          "--save-baseline" => bench.save_baseline = true,
+          "--threshold" => bench.threshold = parse_number(flag, args.next())?,
+          _ => return Ok(false),
+        }
+        Ok(true)
+      })?;
+      Ok(Command::Bench(bench))
+    }
+    Some("verify") => {
+      let mut answers = None;
+      let run = parse_run(&mut args, |flag, args| match flag {
+        "--answers" => match args.next() {
+          Some(path) => {
+            answers = Some(PathBuf::from(path));
+            Ok(true)
+          }
+          None => Err("--answers needs a path".to_string()),
+        },
+        "--format" => Err("verify does not take --format".to_string()),
+        _ => Ok(false),
+      })?;
+      if answers.is_some() && run.day.is_none() {
+        return Err("--answers needs a DAY".to_string());
+      }
+      Ok(Command::Verify(VerifyArgs { run, answers }))
+    }
+    Some("batch") => {
+      // DAY is the first positional argument, which parse_run handles
+      let (mut positional, mut dir) = (0, None);
+      let run = parse_run(&mut args, |arg, _| {
+        if arg.starts_with('-') {
+          return Ok(false);
+        }
+        positional += 1;
+        match positional {
+          2 => dir = Some(PathBuf::from(arg)),
+          _ => return Ok(false),
+        }
+        Ok(true)
+      })?;
+      if run.day.is_none() || dir.is_none() {
+        return Err("batch needs a DAY and a DIR".to_string());
+      }
+      if run.input.is_some() {
+        return Err("batch reads its inputs from DIR, not --input".to_string());
+      }
+      Ok(Command::Batch(BatchArgs {
+        run,
+        dir: dir.unwrap(),
+      }))
+    }
+    Some("new-day") => {
+      let day = parse_number("DAY", args.next())?;
+      if day == 0 {
+        return Err("day must be at least 1".to_string());
+      }
+      match args.next() {
+        Some(arg) => Err(format!("unexpected argument '{}'", arg)),
+        None => Ok(Command::NewDay(day)),
+      }
+    }
+    Some("list") => Ok(Command::List),
+    Some("help") | Some("--help") | Some("-h") => Ok(Command::Help),
+    Some(other) => Err(format!("unknown command '{}'", other)),
+  }
+}
+
+/// Parses the options shared by every command that runs days. `extra` gets
+/// first look at each flag and returns whether it consumed it.
+fn parse_run<I, F>(args: &mut I, mut extra: F) -> Result<RunArgs, String>
+where
+  I: Iterator<Item = String>,
+  F: FnMut(&str, &mut I) -> Result<bool, String>,
+{
+  let mut run = RunArgs::default();
+  while let Some(arg) = args.next() {
+    if extra(&arg, args)? {
+      continue;
+    }
+    match arg.as_str() {
+      "--part" | "-p" => {
+        let part = parse_number(&arg, args.next())?;
+        if part != 1 && part != 2 {
+          return Err(format!("--part must be 1 or 2, got {}", part));
+        }
+        run.part = Some(part);
+      }
+      "--input" | "-i" => match args.next() {
+        Some(path) => run.input = Some(Source::from_arg(&path)),
+        None => return Err("--input needs a path".to_string()),
+      },
+      "--data-dir" => match args.next() {
+        Some(dir) => run.data_dir = Some(PathBuf::from(dir)),
+        None => return Err("--data-dir needs a directory".to_string()),
+      },
+      "--format" => match args.next() {
+        Some(format) => run.format = format.parse()?,
+        None => return Err("--format needs a value".to_string()),
+      },
+      "--report-normalization" => run.report_normalization = true,
+      "--config" => match args.next() {
+        Some(path) => run.config = Some(PathBuf::from(path)),
+        None => return Err("--config needs a path".to_string()),
+      },
+      "--set" => match args.next() {
+        Some(setting) => {
+          Config::default().set_arg(&setting)?;
+          run.settings.push(setting);
+        }
+        None => return Err("--set needs a setting like 'day7.workers=2'".to_string()),
+      },
+      flag if flag.starts_with('-') => return Err(format!("unknown option '{}'", flag)),
+      _ if run.day.is_none() => run.day = Some(parse_number("DAY", Some(arg))?),
+      _ => return Err(format!("unexpected argument '{}'", arg)),
+    }
+  }
+  if run.input.is_some() && run.day.is_none() {
+    return Err("--input needs a DAY".to_string());
+  }
+  Ok(run)
+}
+
+fn parse_number<T: FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+  let value = value.ok_or_else(|| format!("{} needs a value", name))?;
+  value
+    .parse()
+    .map_err(|_| format!("{} must be a number, got '{}'", name, value))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn args(s: &str) -> Vec<String> {
+    s.split_whitespace().map(String::from).collect()
+  }
+
+  #[test]
+  fn no_args_runs_everything() {
+    assert_eq!(parse(args("")), Ok(Command::Run(RunArgs::default())));
+  }
+
+  #[test]
+  fn run_day_part_input() {
+    let expected = RunArgs {
+      day: Some(3),
+      part: Some(2),
+      input: Some(Source::File(PathBuf::from("in.txt"))),
+      ..RunArgs::default()
+    };
+    assert_eq!(
+      parse(args("run 3 --part 2 --input in.txt")),
+      Ok(Command::Run(expected))
+    );
+  }
+
+  #[test]
+  fn stdin_and_data_dir() {
+    match parse(args("run 1 --input - --data-dir inputs")) {
+      Ok(Command::Run(run)) => {
+        assert_eq!(run.input, Some(Source::Stdin));
+        assert_eq!(run.data_dir, Some(PathBuf::from("inputs")));
+        assert!(!run.report_normalization);
+      }
+      other => panic!("unexpected {:?}", other),
+    }
+    match parse(args("verify 2 -i - --report-normalization")) {
+      Ok(Command::Verify(verify)) => assert!(verify.run.report_normalization),
+      other => panic!("unexpected {:?}", other),
+    }
+  }
+
+  #[test]
+  fn bench() {
+    match parse(args("bench 6 -n 50 --save-baseline --threshold 2.5")) {
+      Ok(Command::Bench(bench)) => {
+        assert_eq!(bench.run.day, Some(6));
+        assert_eq!(bench.iterations, 50);
+        assert!(bench.save_baseline);
+        assert_eq!(bench.threshold, 2.5);
+        assert_eq!(bench.baseline, PathBuf::from("aoc-baseline.tsv"));
+      }
+      other => panic!("unexpected {:?}", other),
+    }
+    assert!(parse(args("run 6 -n 50")).is_err());
+  }
+
+  #[test]
+  fn verify() {
+    let expected = VerifyArgs {
+      run: RunArgs {
+        day: Some(4),
+        input: Some(Source::File(PathBuf::from("ex.txt"))),
+        ..RunArgs::default()
+      },
+      answers: Some(PathBuf::from("ex.answers")),
+    };
+    assert_eq!(
+      parse(args("verify 4 -i ex.txt --answers ex.answers")),
+      Ok(Command::Verify(expected))
+    );
+    assert!(parse(args("verify --answers ex.answers")).is_err());
+  }
+
+  #[test]
+  fn batch() {
+    match parse(args("batch 3 inputs/day3 -p 1 --format csv")) {
+      Ok(Command::Batch(batch)) => {
+        assert_eq!(batch.run.day, Some(3));
+        assert_eq!(batch.run.part, Some(1));
+        assert_eq!(batch.run.format, Format::Csv);
+        assert_eq!(batch.dir, PathBuf::from("inputs/day3"));
+      }
+      other => panic!("unexpected {:?}", other),
+    }
+    assert!(parse(args("batch 3")).is_err());
+    assert!(parse(args("batch 3 dir extra")).is_err());
+    assert!(parse(args("batch 3 dir -i in.txt")).is_err());
+  }
+
+  #[test]
+  fn format() {
+    match parse(args("run --format json")) {
+      Ok(Command::Run(run)) => assert_eq!(run.format, Format::Json),
+      other => panic!("unexpected {:?}", other),
+    }
+    assert!(parse(args("run --format yaml")).is_err());
+    assert!(parse(args("verify --format json")).is_err());
+  }
+
+  #[test]
+  fn jobs() {
+    match parse(args("run -j 3")) {
+      Ok(Command::Run(run)) => assert_eq!(run.jobs, 3),
+      other => panic!("unexpected {:?}", other),
+    }
+    assert!(parse(args("bench -j 3")).is_err());
+  }
+
+  #[test]
+  fn profile_and_trace() {
+    match parse(args("run 2 --profile --trace out.json")) {
+      Ok(Command::Run(run)) => {
+        assert!(run.profile);
+        assert_eq!(run.trace, Some(PathBuf::from("out.json")));
+      }
+      other => panic!("unexpected {:?}", other),
+    }
+    assert!(parse(args("run --trace")).is_err());
+    assert!(parse(args("verify --profile")).is_err());
+  }
+
+  #[test]
+  fn config_and_settings() {
+    match parse(args(
+      "verify 7 --config examples.toml --set day7.workers=2 --set day7.base_time=0",
+    )) {
+      Ok(Command::Verify(verify)) => {
+        assert_eq!(verify.run.config, Some(PathBuf::from("examples.toml")));
+        assert_eq!(
+          verify.run.settings,
+          vec!["day7.workers=2", "day7.base_time=0"]
+        );
+      }
+      other => panic!("unexpected {:?}", other),
+    }
+    assert!(parse(args("run --set workers=2")).is_err());
+    assert!(parse(args("run --set")).is_err());
+  }
+
+  #[test]
+  fn timeout() {
+    match parse(args("run --timeout 1.5")) {
+      Ok(Command::Run(run)) => assert_eq!(run.timeout, Some(Duration::from_millis(1500))),
+      other => panic!("unexpected {:?}", other),
+    }
+    assert!(parse(args("run --timeout 0")).is_err());
+    assert!(parse(args("run --timeout soon")).is_err());
+    assert!(parse(args("run --timeout 1e30")).is_err());
+    assert!(parse(args("run --timeout inf")).is_err());
+  }
+
+  #[test]
+  fn new_day() {
+    assert_eq!(parse(args("new-day 8")), Ok(Command::NewDay(8)));
+    assert!(parse(args("new-day")).is_err());
+    assert!(parse(args("new-day 0")).is_err());
+    assert!(parse(args("new-day 8 9")).is_err());
+  }
+
+  #[test]
+  fn rejects_bad_args() {
+    assert!(parse(args("run 3 --part 3")).is_err());
+    assert!(parse(args("run --input in.txt")).is_err());
+    assert!(parse(args("run 3 4")).is_err());
+    assert!(parse(args("frobnicate")).is_err());
+  }
+}
@@ -0,0 +1,457 @@
+use crate::answers::{Answers, Verdict};
+use crate::baseline::Baseline;
+use crate::bench;
+use crate::cli::{BatchArgs, BenchArgs, RunArgs, VerifyArgs};
+use crate::input::{self, Loader, Source};
+use crate::memory::{self, AllocStats};
+use crate::output::{Format, Value, Writer};
+use crate::parse::ParseError;
+use crate::pool;
+use crate::profile;
+use crate::solution::{Entry, Registry};
+use crate::timeout;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+fn print_answer(day: u8, part: u8, answer: Option<String>) {
+  match answer {
+    Some(answer) => println!("day {} part {}: {}", day, part, answer),
+    None => println!("day {} part {}: no answer", day, part),
+  }
+}
+
+/// How long parsing or a part took, and what it allocated when the
+/// `alloc-stats` feature is on.
+struct Phase {
+  elapsed: Duration,
+  memory: AllocStats,
+}
+
+fn phase<T, F: FnOnce() -> T>(name: &str, f: F) -> (T, Phase) {
+  let ((result, elapsed), memory) = profile::span(name, || memory::measure(|| bench::time(f)));
+  (result, Phase { elapsed, memory })
+}
+
+/// A day's answers, with how parsing and each part went.
+struct DayResult {
+  parse: Phase,
+  parts: Vec<(u8, Option<String>, Phase)>,
+}
+
+fn run_day(entry: &Entry, contents: &str, parts: (bool, bool)) -> Result<DayResult, ParseError> {
+  let (input, parse) = phase("parse", || entry.solution.parse(contents));
+  let input = input?;
+  let parts = [(1, parts.0), (2, parts.1)]
+    .iter()
+    .filter(|&&(_, run)| run)
+    .map(|&(part, _)| {
+      let (answer, phase) = phase(&format!("part{}", part), || entry.solve(part, &*input));
+      (part, answer, phase)
+    })
+    .collect();
+  Ok(DayResult { parse, parts })
+}
+
+/// Reads `source` and normalizes it, noting on stderr what had to change if
+/// the user asked.
+fn load_input(loader: &Loader, source: &Source, report: bool) -> Result<String, String> {
+  let raw = loader.load(source).map_err(|e| e.to_string())?;
+  let (contents, changes) = input::normalize(&raw);
+  if report && !changes.is_empty() {
+    eprintln!("note: {}: {}", loader.describe(source), changes);
+  }
+  Ok(contents)
+}
+
+/// A day to run along with its input and the name to report it under.
+struct Job<'a> {
+  entry: &'a Entry,
+  name: String,
+  path: Option<PathBuf>,
+  contents: String,
+}
+
+fn load_jobs<'a>(registry: &'a Registry, args: &RunArgs) -> Result<Vec<Job<'a>>, String> {
+  let entries: Vec<&Entry> = match args.day {
+    Some(day) => vec![registry
+      .get(day)
+      .ok_or_else(|| format!("day {} is not implemented", day))?],
+    None => registry.iter().collect(),
+  };
+  let loader = Loader::new(args.data_dir.clone());
+  entries
+    .into_iter()
+    .map(|entry| {
+      let source = args.input.clone().unwrap_or(Source::Day(entry.day));
+      let contents = load_input(&loader, &source, args.report_normalization)?;
+      Ok(Job {
+        entry,
+        name: loader.describe(&source),
+        path: loader.path(&source),
+        contents,
+      })
+    })
+    .collect()
+}
+
+fn parse_failures(failed: usize) -> Result<(), String> {
+  match failed {
+    0 => Ok(()),
+    n => Err(format!("{} day(s) could not parse their input", n)),
+  }
+}
+
+/// `aoc run`: prints each day's answers and how long it took.
+pub fn run(registry: &Registry, args: &RunArgs) -> Result<(), String> {
+  let jobs = load_jobs(registry, args)?;
+  let mut out = Writer::new(args.format);
+  let mut failed = 0;
+  let mut timed_out = 0;
+  let parts = (args.runs_part(1), args.runs_part(2));
+  let (_, root) = profile::record("Advent of Code Problems", || {
+    let work = |job: &Job| {
+      let (entry, contents) = (job.entry.clone(), job.contents.clone());
+      let day = move || {
+        let name = format!("day{}", entry.day);
+        profile::record(&name, || run_day(&entry, &contents, parts))
+      };
+      match args.timeout {
+        Some(limit) => timeout::with_limit(limit, day),
+        None => Some(day()),
+      }
+    };
+    pool::run_ordered(&jobs, args.jobs, work, |job, outcome| {
+      let day = job.entry.day;
+      let (result, span) = match outcome {
+        Some(outcome) => outcome,
+        None => {
+          let limit = args.timeout.unwrap_or_default();
+          match args.format {
+            Format::Text => println!("day {}: timed out after {:?}", day, limit),
+            _ => eprintln!("error: day {} timed out after {:?}", day, limit),
+          }
+          timed_out += 1;
+          return;
+        }
+      };
+      let elapsed = span.elapsed;
+      profile::attach(span);
+      match result {
+        Ok(result) if args.format == Format::Text => {
+          let mut memory = vec![("parse".to_string(), result.parse.memory)];
+          for (part, answer, phase) in result.parts {
+            print_answer(day, part, answer);
+            memory.push((format!("part{}", part), phase.memory));
+          }
+          if memory::enabled() {
+            for (name, stats) in memory {
+              println!("  {}: {}", name, stats);
+            }
+          }
+        }
+        Ok(result) => {
+          let hash = input::hash(&job.contents);
+          for (part, answer, phase) in result.parts {
+            let mut record = vec![
+              ("day", day.into()),
+              ("part", part.into()),
+              ("answer", answer.into()),
+              ("parse_ns", result.parse.elapsed.into()),
+              ("duration_ns", phase.elapsed.into()),
+              ("input_hash", hash.as_str().into()),
+            ];
+            if memory::enabled() {
+              record.extend(vec![
+                ("allocs", phase.memory.allocations.into()),
+                ("alloc_bytes", phase.memory.bytes.into()),
+                ("peak_bytes", phase.memory.peak.into()),
+              ]);
+            }
+            out.write(&record);
+          }
+        }
+        Err(e) => {
+          eprintln!("error: {}", e.in_file(job.name.as_str()));
+          failed += 1;
+        }
+      }
+      if args.format == Format::Text {
+        println!("Ran day{} in {:?}", day, elapsed);
+      }
+    });
+  });
+  if args.format == Format::Text {
+    println!("Ran Advent of Code Problems in {:?}", root.elapsed);
+  }
+  if args.profile {
+    // keep machine-readable stdout clean
+    match args.format {
+      Format::Text => println!("\n{}", root),
+      _ => eprintln!("{}", root),
+    }
+  }
+  if let Some(path) = &args.trace {
+    fs::write(path, root.to_chrome_trace())
+      .map_err(|e| format!("could not write {}: {}", path.display(), e))?;
+  }
+  parse_failures(failed)?;
+  match timed_out {
+    0 => Ok(()),
+    n => Err(format!("{} day(s) timed out", n)),
+  }
+}
+
+/// `aoc bench`: times each phase repeatedly and compares against the baseline.
+pub fn bench(registry: &Registry, args: &BenchArgs) -> Result<(), String> {
+  let jobs = load_jobs(registry, &args.run)?;
+  let mut baseline = Baseline::load(&args.baseline)?;
+  let parts = (args.run.runs_part(1), args.run.runs_part(2));
+  let mut failed = 0;
+  let mut regressed = 0;
+  let mut total = Duration::default();
+  let mut out = Writer::new(args.run.format);
+  let text = args.run.format == Format::Text;
+  if text {
+    bench::print_header();
+  }
+  for job in &jobs {
+    match bench::bench_day(job.entry, &job.contents, parts, args.iterations) {
+      Ok(day) => {
+        let hash = input::hash(&job.contents);
+        for (phase, stats) in day.phases() {
+          let change = baseline.compare(day.day, phase, &stats, args.threshold);
+          if change.as_ref().is_some_and(|c| c.regressed) {
+            regressed += 1;
+          }
+          if text {
+            bench::print_row(day.day, phase, &stats, change.as_ref());
+          } else {
+            out.write(&vec![
+              ("day", day.day.into()),
+              ("phase", phase.into()),
+              ("samples", stats.samples.into()),
+              ("min_ns", stats.min.into()),
+              ("median_ns", stats.median.into()),
+              ("mean_ns", stats.mean.into()),
+              ("stddev_ns", stats.stddev.into()),
+              (
+                "baseline_median_ns",
+                change.as_ref().map(|c| c.baseline).into(),
+              ),
+              ("change_pct", change.as_ref().map(|c| c.percent).into()),
+              ("regressed", change.as_ref().map(|c| c.regressed).into()),
+              ("input_hash", hash.as_str().into()),
+            ]);
+          }
+          total += stats.median;
+        }
+        if args.save_baseline {
+          baseline.record(&day);
+        }
+      }
+      Err(e) => {
+        eprintln!("error: {}", e.in_file(job.name.as_str()));
+        failed += 1;
+      }
+    }
+  }
+  if text {
+    println!("sum of medians: {:.2?}", total);
+  }
+  if args.save_baseline {
+    baseline.save(&args.baseline)?;
+    eprintln!("saved baseline to {}", args.baseline.display());
+  }
+  parse_failures(failed)?;
+  match regressed {
+    0 => Ok(()),
+    n => Err(format!(
+      "{} phase(s) regressed by more than {}%",
+      n, args.threshold
+    )),
+  }
+}
+
+/// `aoc verify`: checks each answer against the input's answers file.
+pub fn verify(registry: &Registry, args: &VerifyArgs) -> Result<(), String> {
+  let jobs = load_jobs(registry, &args.run)?;
+  let (mut passed, mut failed, mut unchecked) = (0, 0, 0);
+  for job in &jobs {
+    let answers_path = args
+      .answers
+      .clone()
+      .or_else(|| job.path.as_deref().map(Answers::path_for));
+    let answers = match &answers_path {
+      Some(path) => Answers::load(path)?.unwrap_or_default(),
+      None => Answers::default(),
+    };
+    let input = match job.entry.solution.parse(&job.contents) {
+      Ok(input) => input,
+      Err(e) => {
+        eprintln!("error: {}", e.in_file(job.name.as_str()));
+        failed += (1..=2).filter(|&part| args.run.runs_part(part)).count();
+        continue;
+      }
+    };
+    for part in 1..=2 {
+      if !args.run.runs_part(part) {
+        continue;
+      }
+      let actual = job.entry.solve(part, &*input);
+      let verdict = Verdict::check(answers.expected(part), actual.as_deref());
+      match verdict {
+        Verdict::Pass => passed += 1,
+        Verdict::Fail { .. } => failed += 1,
+        Verdict::Unchecked => unchecked += 1,
+      }
+      println!(
+        "day {} part {}: {} {}",
+        job.entry.day,
+        part,
+        actual.as_deref().unwrap_or("no answer"),
+        verdict
+      );
+    }
+  }
+  println!(
+    "{} passed, {} failed, {} unchecked",
+    passed, failed, unchecked
+  );
+  match failed {
+    0 => Ok(()),
+    n => Err(format!("{} answer(s) did not match", n)),
+  }
+}
+
+/// Column-aligned text table; `right` marks the columns aligned right.
+fn print_table(header: &[&str], right: &[bool], rows: &[Vec<String>]) {
+  let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+  for row in rows {
+    for (width, cell) in widths.iter_mut().zip(row) {
+      *width = (*width).max(cell.chars().count());
+    }
+  }
+  let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+  for row in std::iter::once(&header).chain(rows) {
+    let cells: Vec<String> = row
+      .iter()
+      .zip(&widths)
+      .zip(right)
+      .map(|((cell, &width), &right)| {
+        if right {
+          format!("{:>width$}", cell, width = width)
+        } else {
+          format!("{:<width$}", cell, width = width)
+        }
+      })
+      .collect();
+    println!("{}", cells.join("  ").trim_end());
+  }
+}
+
+/// `aoc batch`: runs one day against every input in a directory, checking
+/// each against its answers file when there is one.
+pub fn batch(registry: &Registry, args: &BatchArgs) -> Result<(), String> {
+  let day = args.run.day.expect("batch always has a DAY");
+  let entry = registry
+    .get(day)
+    .ok_or_else(|| format!("day {} is not implemented", day))?;
+  let inputs = input::inputs_in(&args.dir).map_err(|e| e.to_string())?;
+  if inputs.is_empty() {
+    return Err(format!("no inputs in {}", args.dir.display()));
+  }
+  let loader = Loader::new(args.run.data_dir.clone());
+  let parts = (args.run.runs_part(1), args.run.runs_part(2));
+  let text = args.run.format == Format::Text;
+  let mut out = Writer::new(args.run.format);
+  let mut rows = vec![];
+  let (mut passed, mut failed, mut unchecked) = (0, 0, 0);
+  for path in &inputs {
+    let name = path.file_name().map_or_else(
+      || path.display().to_string(),
+      |n| n.to_string_lossy().into_owned(),
+    );
+    let source = Source::File(path.clone());
+    let answers = Answers::load(&Answers::path_for(path))?.unwrap_or_default();
+    let contents = load_input(&loader, &source, args.run.report_normalization)?;
+    let hash = input::hash(&contents);
+    let result = match run_day(entry, &contents, parts) {
+      Ok(result) => result,
+      Err(e) => {
+        eprintln!("error: {}", e.in_file(loader.describe(&source)));
+        failed += 1;
+        if text {
+          let cells = [name.as_str(), "-", "", "", "", "parse error"];
+          rows.push(cells.iter().map(|c| c.to_string()).collect());
+        } else {
+          out.write(&vec![
+            ("input", name.as_str().into()),
+            ("part", Value::Null),
+            ("answer", Value::Null),
+            ("expected", Value::Null),
+            ("status", "parse_error".into()),
+            ("parse_ns", Value::Null),
+            ("duration_ns", Value::Null),
+            ("input_hash", hash.as_str().into()),
+          ]);
+        }
+        continue;
+      }
+    };
+    for (part, answer, phase) in result.parts {
+      let expected = answers.expected(part);
+      let verdict = Verdict::check(expected, answer.as_deref());
+      let status = match verdict {
+        Verdict::Pass => {
+          passed += 1;
+          "pass"
+        }
+        Verdict::Fail { .. } => {
+          failed += 1;
+          "fail"
+        }
+        Verdict::Unchecked => {
+          unchecked += 1;
+          "unchecked"
+        }
+      };
+      if text {
+        rows.push(vec![
+          name.clone(),
+          part.to_string(),
+          answer.unwrap_or_else(|| "no answer".to_string()),
+          format!("{:.2?}", result.parse.elapsed),
+          format!("{:.2?}", phase.elapsed),
+          verdict.to_string(),
+        ]);
+      } else {
+        out.write(&vec![
+          ("input", name.as_str().into()),
+          ("part", part.into()),
+          ("answer", answer.into()),
+          ("expected", expected.into()),
+          ("status", status.into()),
+          ("parse_ns", result.parse.elapsed.into()),
+          ("duration_ns", phase.elapsed.into()),
+          ("input_hash", hash.as_str().into()),
+        ]);
+      }
+    }
+  }
+  if text {
+    print_table(
+      &["input", "part", "answer", "parse", "time", "status"],
+      &[false, true, false, true, true, false],
+      &rows,
+    );
+    println!(
+      "{} passed, {} failed, {} unchecked",
+      passed, failed, unchecked
+    );
+  }
+  match failed {
+    0 => Ok(()),
+    n => Err(format!("{} answer(s) or input(s) failed", n)),
+  }
+}
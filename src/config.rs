@@ -0,0 +1,183 @@
+use crate::parse::{load_optional, parse_lines, ParseError};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::path::Path;
+
+/// Config file read from the current directory when `--config` isn't given.
+pub const DEFAULT_PATH: &str = "aoc.toml";
+
+/// Puzzle parameters, one table per day, read from a small subset of TOML:
+///
+/// ```text
+/// # the puzzle's example uses 2 workers and no base time
+/// [day7]
+/// workers = 2
+/// base_time = 0
+/// ```
+///
+/// Only `[dayN]` tables with integer values are supported.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+  days: BTreeMap<u8, Params>,
+}
+
+/// The settings for one day. Days take the ones they know with `get`, and
+/// `finish` rejects whatever is left so typos don't go unnoticed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params {
+  day: u8,
+  values: BTreeMap<String, i64>,
+}
+
+impl Config {
+  /// Reads `path`. A missing file is an empty config unless `required`.
+  pub fn load(path: &Path, required: bool) -> Result<Config, String> {
+    match load_optional(path, Config::parse)? {
+      Some(config) => Ok(config),
+      None if required => Err(format!("{} does not exist", path.display())),
+      None => Ok(Config::default()),
+    }
+  }
+
+  pub fn parse(contents: &str) -> Result<Config, ParseError> {
+    let mut config = Config::default();
+    let mut day = None;
+    parse_lines(contents, |l| {
+      let l = l.split('#').next().unwrap_or("").trim();
+      if l.is_empty() {
+        return Ok(());
+      }
+      if let Some(table) = l.strip_prefix('[') {
+        let name = table
+          .strip_suffix(']')
+          .ok_or_else(|| ParseError::new("expected a table like '[day7]'"))?;
+        day = Some(parse_day(name.trim())?);
+        return Ok(());
+      }
+      let day = day.ok_or_else(|| ParseError::new("settings must be inside a '[dayN]' table"))?;
+      let (key, value) = l
+        .split_once('=')
+        .ok_or_else(|| ParseError::new("expected 'key = value'"))?;
+      config.set(day, key.trim(), parse_value(value.trim())?);
+      Ok(())
+    })?;
+    Ok(config)
+  }
+
+  pub fn set(&mut self, day: u8, key: &str, value: i64) {
+    let params = self.days.entry(day).or_insert_with(|| Params {
+      day,
+      ..Params::default()
+    });
+    params.values.insert(key.to_string(), value);
+  }
+
+  /// Applies a command-line override like `day7.workers=2`.
+  pub fn set_arg(&mut self, arg: &str) -> Result<(), String> {
+    let err = || format!("expected a setting like 'day7.workers=2', got '{}'", arg);
+    let (name, value) = arg.split_once('=').ok_or_else(err)?;
+    let (day, key) = name.split_once('.').ok_or_else(err)?;
+    let day = parse_day(day).map_err(|_| err())?;
+    let value = parse_value(value).map_err(|e| format!("{}: {}", arg, e.message))?;
+    self.set(day, key, value);
+    Ok(())
+  }
+
+  /// Removes and returns the settings for `day`, empty if there are none.
+  pub fn take(&mut self, day: u8) -> Params {
+    self.days.remove(&day).unwrap_or(Params {
+      day,
+      ..Params::default()
+    })
+  }
+
+  /// Fails if any setting was never taken.
+  pub fn finish(self) -> Result<(), String> {
+    self.days.into_values().try_for_each(Params::finish)
+  }
+}
+
+fn parse_day(name: &str) -> Result<u8, ParseError> {
+  name
+    .strip_prefix("day")
+    .and_then(|n| n.parse().ok())
+    .ok_or_else(|| ParseError::new(format!("expected a day like 'day7', found '{}'", name)))
+}
+
+fn parse_value(value: &str) -> Result<i64, ParseError> {
+  value.replace('_', "").parse().map_err(|_| {
+    ParseError::new(format!(
+      "only integer values are supported, found '{}'",
+      value
+    ))
+  })
+}
+
+impl Params {
+  /// The setting `key`, or `default` if it isn't set.
+  pub fn get<T: TryFrom<i64>>(&mut self, key: &str, default: T) -> Result<T, String> {
+    match self.values.remove(key) {
+      None => Ok(default),
+      Some(value) => T::try_from(value)
+        .map_err(|_| format!("day{}.{} = {} is out of range", self.day, key, value)),
+    }
+  }
+
+  /// Fails if a setting was never asked for.
+  pub fn finish(self) -> Result<(), String> {
+    match self.values.keys().next() {
+      None => Ok(()),
+      Some(key) => Err(format!("day {} has no setting '{}'", self.day, key)),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parse_tables() {
+    let mut config = Config::parse(
+      "# examples\n[day7]\nworkers = 2 # not 5\nbase_time = 0\n\n[ day6 ]\nmax_total_distance = 1_000\n",
+    )
+    .unwrap();
+    let mut day7 = config.take(7);
+    assert_eq!(day7.get("workers", 5usize), Ok(2));
+    assert_eq!(day7.get("base_time", 60u32), Ok(0));
+    assert_eq!(day7.finish(), Ok(()));
+    assert_eq!(config.take(6).get("max_total_distance", 0u32), Ok(1000));
+    assert_eq!(
+      config.take(1),
+      Params {
+        day: 1,
+        ..Params::default()
+      }
+    );
+  }
+
+  #[test]
+  fn parse_errors() {
+    assert_eq!(Config::parse("workers = 2").unwrap_err().line, 1);
+    assert_eq!(Config::parse("[day7]\nworkers = two").unwrap_err().line, 2);
+    assert!(Config::parse("[seven]").is_err());
+    assert!(Config::parse("[day7]\nworkers").is_err());
+  }
+
+  #[test]
+  fn overrides_and_leftovers() {
+    let mut config = Config::parse("[day7]\nworkers = 2\n").unwrap();
+    config.set_arg("day7.workers=3").unwrap();
+    config.set_arg("day6.typo=1").unwrap();
+    assert!(config.set_arg("workers=3").is_err());
+    assert!(config.set_arg("day7.workers=many").is_err());
+    assert_eq!(config.take(7).get("workers", 5u8), Ok(3));
+    assert_eq!(
+      config.finish(),
+      Err("day 6 has no setting 'typo'".to_string())
+    );
+    let mut range = Config::default();
+    range.set(7, "workers", -1);
+    assert!(range.take(7).get("workers", 5usize).is_err());
+  }
+}
@@ -1,18 +1,422 @@
-use std::collections::HashSet;
+use crate::parse::ParseError;
+use crate::solution::Solution;
+use crate::utils::ScanInt;
+use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap};
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::num::ParseIntError;
+use std::str::FromStr;
 
-pub fn run() {
-  let contents = include_str!("../data/day1.txt");
-  let values = contents.lines().map(|l| l.parse::<i32>().unwrap());
-  println!("day 1 part 1: {}", values.clone().sum::<i32>());
+/// Frequencies accumulate in `T`; the registry uses `i64`, and `i128` takes
+/// anything that fits in a line.
+pub struct Day1<T = i64>(PhantomData<T>);
 
-  let mut seen = HashSet::<i32>::default();
-  let mut freq = 0;
-  for v in values.clone().cycle() {
-    freq += v;
-    let inserted = seen.insert(freq);
-    if !inserted {
-      break;
+impl<T> Default for Day1<T> {
+  fn default() -> Day1<T> {
+    Day1(PhantomData)
+  }
+}
+
+impl<T: Freq> Solution for Day1<T> {
+  type Input = Trajectory<T>;
+  type Part1 = T;
+  type Part2 = T;
+
+  /// Changes can be separated by newlines, commas or other whitespace, so
+  /// the puzzle's `+1, -2, +3, +1` can be used as it is.
+  fn parse(&self, contents: &str) -> Result<Trajectory<T>, ParseError> {
+    let re = Regex::new(r"[^,\s]+").unwrap();
+    let (offsets, changes): (Vec<usize>, Vec<T>) = re
+      .find_iter(contents)
+      .map(|m| {
+        m.as_str()
+          .parse::<T>()
+          .map(|change| (m.start(), change))
+          .map_err(|e| {
+            ParseError::new(format!("invalid frequency change: {}", e))
+              .at_offset(contents, m.start())
+          })
+      })
+      .collect::<Result<Vec<_>, _>>()?
+      .into_iter()
+      .unzip();
+    Trajectory::new(&changes).map_err(|Overflow { index }| {
+      ParseError::new(format!("the frequency overflows {} here", T::NAME))
+        .at_offset(contents, offsets[index])
+    })
+  }
+
+  fn part1(&self, trajectory: &Trajectory<T>) -> Option<T> {
+    Some(trajectory.drift())
+  }
+
+  fn part2(&self, trajectory: &Trajectory<T>) -> Option<T> {
+    trajectory.repeats().next().map(|r| r.freq)
+  }
+}
+
+/// An integer type to accumulate frequencies in. Sums are checked rather
+/// than left to wrap, so pick one wide enough for the input.
+pub trait Freq:
+  ScanInt + FromStr<Err = ParseIntError> + Ord + Hash + Debug + Display + Send + Sync
+{
+  const ZERO: Self;
+  fn checked_add(self, other: Self) -> Option<Self>;
+  /// `self + passes * drift`, if it fits.
+  fn shifted(self, passes: u128, drift: Self) -> Option<Self>;
+  /// The remainder after dividing by `drift`, never negative.
+  fn residue(self, drift: Self) -> Self;
+  /// How many passes of `drift` it takes to get from `self` to `to`, which
+  /// must differ from it by a multiple of `drift`.
+  fn passes_to(self, to: Self, drift: Self) -> u128;
+}
+
+macro_rules! freq {
+  ($($t:ty: $u:ty),*) => {$(
+    impl Freq for $t {
+      const ZERO: $t = 0;
+
+      fn checked_add(self, other: $t) -> Option<$t> {
+        <$t>::checked_add(self, other)
+      }
+
+      fn shifted(self, passes: u128, drift: $t) -> Option<$t> {
+        let by = <$u>::try_from(passes.checked_mul(drift.unsigned_abs() as u128)?).ok()?;
+        if drift < 0 {
+          self.checked_sub_unsigned(by)
+        } else {
+          self.checked_add_unsigned(by)
+        }
+      }
+
+      fn residue(self, drift: $t) -> $t {
+        // only MIN % -1 overflows, and that's 0
+        self.checked_rem_euclid(drift).unwrap_or(0)
+      }
+
+      fn passes_to(self, to: $t, drift: $t) -> u128 {
+        if drift.unsigned_abs() == 1 {
+          self.abs_diff(to) as u128
+        } else {
+          // exact, since both have the same remainder, and can't overflow
+          self.div_euclid(drift).abs_diff(to.div_euclid(drift)) as u128
+        }
+      }
+    }
+  )*};
+}
+
+freq!(i32: u32, i64: u64, i128: u128);
+
+/// Applying `changes[index]` takes the frequency out of range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow {
+  pub index: usize,
+}
+
+/// Where the frequency goes as a list of changes is applied over and over,
+/// starting from 0, worked out from the first pass alone.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trajectory<T> {
+  /// 0, then the frequency after each change of the first pass.
+  freqs: Vec<T>,
+}
+
+/// A frequency reached again, by applying `changes[index]` on pass `cycle`
+/// (both counted from 0).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repeat<T> {
+  pub freq: T,
+  pub cycle: u128,
+  pub index: usize,
+}
+
+impl<T: Freq> Trajectory<T> {
+  /// Follows the first pass, failing if a frequency doesn't fit in `T`.
+  pub fn new(changes: &[T]) -> Result<Trajectory<T>, Overflow> {
+    let mut freqs = Vec::with_capacity(changes.len() + 1);
+    let mut freq = T::ZERO;
+    freqs.push(freq);
+    for (index, &change) in changes.iter().enumerate() {
+      freq = freq.checked_add(change).ok_or(Overflow { index })?;
+      freqs.push(freq);
+    }
+    Ok(Trajectory { freqs })
+  }
+
+  /// The prefix sums: 0, then the frequency after each change of the first
+  /// pass, ending with the drift.
+  pub fn frequencies(&self) -> &[T] {
+    &self.freqs
+  }
+
+  /// How far each full pass moves the frequency.
+  pub fn drift(&self) -> T {
+    self.freqs[self.freqs.len() - 1]
+  }
+
+  /// The lowest frequency ever reached, or `None` if it keeps falling.
+  pub fn min(&self) -> Option<T> {
+    if self.drift() < T::ZERO {
+      None
+    } else {
+      self.freqs.iter().copied().min()
+    }
+  }
+
+  /// The highest frequency ever reached, or `None` if it keeps rising.
+  pub fn max(&self) -> Option<T> {
+    if self.drift() > T::ZERO {
+      None
+    } else {
+      self.freqs.iter().copied().max()
+    }
+  }
+
+  /// For the frequency before each change, the first pass on which it has
+  /// already been reached, if it ever is.
+  ///
+  /// Pass `c` visits the first pass's frequencies shifted by `c * drift`.
+  /// Two of them can only meet if they differ by a multiple of the drift, so
+  /// group them by remainder: ordered in the drift's direction, each
+  /// frequency in a group is next reached by the one before it, `gap / drift`
+  /// passes later, and from then on every pass repeats it again.
+  fn repeats_from(&self) -> Vec<Option<u128>> {
+    let len = self.freqs.len() - 1;
+    let drift = self.drift();
+    let mut repeats_from = vec![None; len];
+    let mut first_seen = HashMap::new();
+    for (idx, &freq) in self.freqs[..len].iter().enumerate() {
+      match first_seen.entry(freq) {
+        Entry::Occupied(_) => repeats_from[idx] = Some(0),
+        Entry::Vacant(e) => {
+          e.insert(idx);
+        }
+      }
+    }
+    if drift == T::ZERO {
+      for pass in repeats_from.iter_mut().filter(|p| p.is_none()) {
+        *pass = Some(1);
+      }
+      return repeats_from;
+    }
+
+    let mut groups: HashMap<T, Vec<(T, usize)>> = HashMap::new();
+    for (&freq, &idx) in &first_seen {
+      groups
+        .entry(freq.residue(drift))
+        .or_default()
+        .push((freq, idx));
+    }
+    for group in groups.values_mut() {
+      group.sort_unstable();
+      if drift < T::ZERO {
+        group.reverse();
+      }
+      for pair in group.windows(2) {
+        let ((from, idx), (to, _)) = (pair[0], pair[1]);
+        repeats_from[idx] = Some(from.passes_to(to, drift));
+      }
+    }
+    repeats_from
+  }
+
+  /// Every step that reaches a frequency seen before, in order; `take` the
+  /// first few. Endless once anything repeats, unless the frequencies leave
+  /// `T`'s range, and empty if nothing ever does.
+  pub fn repeats(&self) -> Repeats<'_, T> {
+    let len = self.freqs.len() as u128 - 1;
+    let next = self
+      .repeats_from()
+      .into_iter()
+      .enumerate()
+      .filter_map(|(idx, pass)| Some(Reverse(pass?.checked_mul(len)? + idx as u128)))
+      .collect();
+    Repeats {
+      trajectory: self,
+      next,
+    }
+  }
+}
+
+pub struct Repeats<'a, T> {
+  trajectory: &'a Trajectory<T>,
+  /// The next repeat of each frequency that has one, as the number of
+  /// changes applied before reaching it.
+  next: BinaryHeap<Reverse<u128>>,
+}
+
+impl<T: Freq> Iterator for Repeats<'_, T> {
+  type Item = Repeat<T>;
+
+  fn next(&mut self) -> Option<Repeat<T>> {
+    let len = self.trajectory.freqs.len() as u128 - 1;
+    loop {
+      let Reverse(step) = self.next.pop()?;
+      let (pass, idx) = (step / len, (step % len) as usize);
+      let freq = match self.trajectory.freqs[idx].shifted(pass, self.trajectory.drift()) {
+        Some(freq) => freq,
+        // out of range from here on
+        None => continue,
+      };
+      if let Some(next) = step.checked_add(len) {
+        self.next.push(Reverse(next));
+      }
+      // reached by the change just before, which may be last pass's last one
+      let applied = step - 1;
+      return Some(Repeat {
+        freq,
+        cycle: applied / len,
+        index: (applied % len) as usize,
+      });
     }
   }
-  println!("day 1 part 2: {}", freq);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashSet;
+
+  fn first_repeat(changes: &[i32]) -> Option<i32> {
+    Trajectory::new(changes)
+      .unwrap()
+      .repeats()
+      .next()
+      .map(|r| r.freq)
+  }
+
+  /// Walks `limit` steps, collecting every step that revisits a frequency.
+  fn walk(changes: &[i32], limit: usize) -> Vec<Repeat<i32>> {
+    let mut seen = HashSet::new();
+    let mut freq = 0;
+    let mut repeats = vec![];
+    for (step, &change) in changes.iter().cycle().take(limit).enumerate() {
+      seen.insert(freq);
+      freq += change;
+      if seen.contains(&freq) {
+        repeats.push(Repeat {
+          freq,
+          cycle: (step / changes.len()) as u128,
+          index: step % changes.len(),
+        });
+      }
+    }
+    repeats
+  }
+
+  #[test]
+  fn separators() {
+    let day = Day1::<i64>::default();
+    for input in &["+1, -2, +3, +1", "+1\n-2\n+3\n+1\n", "1 -2\t+3,\n+1\n"] {
+      let trajectory = Solution::parse(&day, input).unwrap();
+      assert_eq!(trajectory.frequencies(), &[0, 1, -1, 2, 3]);
+      assert_eq!(day.part1(&trajectory), Some(3));
+      assert_eq!(day.part2(&trajectory), Some(2));
+    }
+    let err = Solution::parse(&day, "+1, -2\n+3, 1+, +1").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 5));
+    assert_eq!(err.text, "+3, 1+, +1");
+  }
+
+  #[test]
+  fn examples() {
+    assert_eq!(first_repeat(&[1, -2, 3, 1]), Some(2));
+    assert_eq!(first_repeat(&[1, -1]), Some(0));
+    assert_eq!(first_repeat(&[3, 3, 4, -2, -4]), Some(10));
+    assert_eq!(first_repeat(&[-6, 3, 8, 5, -6]), Some(5));
+    assert_eq!(first_repeat(&[7, 7, -2, -7, -4]), Some(14));
+  }
+
+  #[test]
+  fn never_repeats() {
+    assert_eq!(first_repeat(&[]), None);
+    assert_eq!(first_repeat(&[1]), None);
+    assert_eq!(first_repeat(&[3, -1]), None);
+    assert_eq!(first_repeat(&[-4, 10]), None);
+  }
+
+  #[test]
+  fn matches_walking() {
+    let mut seed = 2018u32;
+    for _ in 0..500 {
+      let changes: Vec<i32> = (0..seed % 7 + 1)
+        .map(|_| {
+          seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+          (seed >> 16) as i32 % 21 - 10
+        })
+        .collect();
+      let walked = walk(&changes, 3_000);
+      assert_eq!(
+        first_repeat(&changes),
+        walked.first().map(|r| r.freq),
+        "{:?}",
+        changes
+      );
+      let trajectory = Trajectory::new(&changes).unwrap();
+      let repeats: Vec<_> = trajectory.repeats().take(20).collect();
+      assert_eq!(repeats[..], walked[..walked.len().min(20)], "{:?}", changes);
+    }
+  }
+
+  #[test]
+  fn trajectory() {
+    let trajectory = Trajectory::new(&[1, -2, 3, 1]).unwrap();
+    assert_eq!(trajectory.frequencies(), &[0, 1, -1, 2, 3]);
+    assert_eq!(trajectory.drift(), 3);
+    assert_eq!((trajectory.min(), trajectory.max()), (Some(-1), None));
+    let repeats: Vec<_> = trajectory.repeats().take(3).collect();
+    assert_eq!(
+      repeats,
+      vec![
+        Repeat {
+          freq: 2,
+          cycle: 1,
+          index: 1
+        },
+        Repeat {
+          freq: 5,
+          cycle: 2,
+          index: 1
+        },
+        Repeat {
+          freq: 8,
+          cycle: 3,
+          index: 1
+        },
+      ]
+    );
+    let flat = Trajectory::new(&[4, -4]).unwrap();
+    assert_eq!((flat.min(), flat.max()), (Some(0), Some(4)));
+    assert_eq!(Trajectory::new(&[-3]).unwrap().repeats().next(), None);
+  }
+
+  #[test]
+  fn overflow() {
+    let input = "+2147483647\n-1\n+2\n";
+    let err = Solution::parse(&Day1::<i32>::default(), input).unwrap_err();
+    assert_eq!((err.line, err.column), (3, 1));
+    assert_eq!(err.message, "the frequency overflows i32 here");
+    let wide = Day1::<i64>::default();
+    let trajectory = Solution::parse(&wide, input).unwrap();
+    assert_eq!(wide.part1(&trajectory), Some(2147483648));
+
+    let day = Day1::<i128>::default();
+    let input = "+9223372036854775807\n+9223372036854775807\n-18446744073709551613\n";
+    let trajectory = Solution::parse(&day, input).unwrap();
+    assert_eq!(day.part1(&trajectory), Some(1));
+    assert_eq!(day.part2(&trajectory), Some(9223372036854775807));
+  }
+
+  #[test]
+  fn repeats_stop_at_the_edge_of_the_range() {
+    let trajectory = Trajectory::new(&[-(1 << 29), 1 << 30]).unwrap();
+    let repeats: Vec<_> = trajectory.repeats().map(|r| r.freq).collect();
+    assert_eq!(repeats, vec![0, 1 << 29, 1 << 30, 3 << 29]);
+  }
 }
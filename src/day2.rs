@@ -1,14 +1,31 @@
-use std::collections::HashMap;
+use crate::parse::ParseError;
+use crate::solution::Solution;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 
-pub fn run() {
-  let contents = include_str!("../data/day2.txt");
-  part1(&contents);
-  part2(&contents);
+pub struct Day2;
+
+impl Solution for Day2 {
+  type Input = String;
+  type Part1 = u32;
+  type Part2 = String;
+
+  fn parse(&self, contents: &str) -> Result<String, ParseError> {
+    Ok(contents.to_string())
+  }
+
+  fn part1(&self, contents: &String) -> Option<u32> {
+    Some(part1(contents))
+  }
+
+  fn part2(&self, contents: &String) -> Option<String> {
+    part2(contents)
+  }
 }
 
 type SeenMap = HashMap<char, u8>;
 
-fn part1(contents: &str) {
+fn part1(contents: &str) -> u32 {
   let (two, three) = contents.lines().fold((0, 0), |(two, three), line| {
     let counts = char_counts(line);
 
@@ -20,7 +37,7 @@ fn part1(contents: &str) {
       (false, false) => (two, three),
     }
   });
-  println!("day 2 part 1: {}", two * three);
+  two * three
 }
 
 fn char_counts(s: &str) -> SeenMap {
@@ -30,33 +47,92 @@ fn char_counts(s: &str) -> SeenMap {
   })
 }
 
-fn common_chars(lhs: &str, rhs: &str) -> String {
-  let mut result = String::with_capacity(lhs.len() - 1);
-  for (lchar, rchar) in lhs.chars().zip(rhs.chars()) {
-    if lchar == rchar {
-      result.push(lchar);
-    }
-  }
-  result
+/// Modulus for the ID hashes, the Mersenne prime 2^61 - 1.
+const MODULUS: u64 = (1 << 61) - 1;
+/// One more than the largest `char`, so no two IDs hash alike before the
+/// modulus kicks in.
+const BASE: u64 = 0x11_0000;
+
+fn mul_mod(a: u64, b: u64) -> u64 {
+  ((a as u128 * b as u128) % MODULUS as u128) as u64
 }
 
-fn part2(contents: &str) {
-  for line in contents.lines() {
-    for other in contents.lines() {
-      let mut diff_count = 0;
-      for (l, r) in line.chars().zip(other.chars()) {
-        if l != r {
-          diff_count += 1;
-          if diff_count > 1 {
-            break;
-          }
-        }
+/// Polynomial hash of `id`, the first character weighted highest.
+fn hash(id: &[char]) -> u64 {
+  id.iter()
+    .fold(0, |h, &c| (mul_mod(h, BASE) + c as u64) % MODULUS)
+}
+
+/// Finds two IDs of the same length that differ in exactly one position and
+/// returns the characters they have in common. For each position, every ID
+/// is hashed with that character taken out, which is O(1) given the ID's
+/// full hash, so candidates meet in a hash map instead of every pair being
+/// compared: O(n·L) rather than O(n²·L). Candidates are checked, so a hash
+/// collision can't give a wrong answer.
+fn part2(contents: &str) -> Option<String> {
+  let mut seen = HashSet::new();
+  // identical IDs would meet at every position
+  let ids: Vec<Vec<char>> = contents
+    .lines()
+    .filter(|l| seen.insert(*l))
+    .map(|l| l.chars().collect())
+    .collect();
+  let hashes: Vec<u64> = ids.iter().map(|id| hash(id)).collect();
+  let max_len = ids.iter().map(Vec::len).max()?;
+  let mut powers = vec![1; max_len];
+  for k in 1..max_len {
+    powers[k] = mul_mod(powers[k - 1], BASE);
+  }
+
+  // the first ID with each masked hash, and any others that only share it
+  // by a hash collision
+  let mut buckets: HashMap<(usize, u64), (usize, Vec<usize>)> = HashMap::with_capacity(ids.len());
+  for pos in 0..max_len {
+    buckets.clear();
+    for (idx, id) in ids.iter().enumerate() {
+      if pos >= id.len() {
+        continue;
       }
-      if diff_count == 1 {
-        println!("day 2 part 2: {}", common_chars(&line, &other));
-        return;
+      let removed = mul_mod(id[pos] as u64, powers[id.len() - 1 - pos]);
+      let masked = (hashes[idx] + MODULUS - removed) % MODULUS;
+      let (first, others) = match buckets.entry((id.len(), masked)) {
+        Entry::Vacant(e) => {
+          e.insert((idx, vec![]));
+          continue;
+        }
+        Entry::Occupied(e) => e.into_mut(),
+      };
+      let matched = std::iter::once(*first)
+        .chain(others.iter().copied())
+        .any(|other| {
+          let other = &ids[other];
+          other[..pos] == id[..pos] && other[pos + 1..] == id[pos + 1..]
+        });
+      if matched {
+        return Some(id[..pos].iter().chain(&id[pos + 1..]).collect());
       }
+      others.push(idx);
     }
   }
-  println!("found nothing");
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn example() {
+    let ids = "abcde\nfghij\nklmno\npqrst\nfguij\naxcye\nwvxyz\n";
+    assert_eq!(part2(ids), Some("fgij".to_string()));
+  }
+
+  #[test]
+  fn needs_exactly_one_difference() {
+    assert_eq!(part2("abc\nabc\nxbz\n"), None);
+    assert_eq!(part2("abc\nabcd\n"), None);
+    assert_eq!(part2("abc\nabc\nabd\n"), Some("ab".to_string()));
+    assert_eq!(part2("héllo\nhéllø\n"), Some("héll".to_string()));
+    assert_eq!(part2(""), None);
+  }
 }
@@ -1,87 +1,136 @@
-use crate::utils;
+use crate::parse::{parse_lines, ParseError};
+use crate::solution::Solution;
+use crate::utils::{self, Grid, Point2};
 use std::collections::HashMap;
 
 #[derive(Debug, Eq, PartialEq, Hash)]
-struct Claim {
-  id: u32,
-  x: u32,
-  y: u32,
-  w: u32,
-  h: u32,
+pub struct Claim {
+  pub id: u32,
+  /// Top-left corner.
+  pub pos: Point2,
+  /// Width and height.
+  pub size: Point2,
 }
 impl Claim {
-  fn new(s: &str) -> Claim {
-    let ints = utils::scan_ints(s);
+  /// Parses a claim like `#1 @ 2,3: 4x5`.
+  pub fn new(s: &str) -> Result<Claim, ParseError> {
+    let ints: Vec<u32> = utils::scan_ints(s)?;
     if ints.len() != 5 {
-      println!("{:?}", ints);
-      panic!("Wrong ints");
+      return Err(ParseError::new(format!(
+        "expected a claim like '#1 @ 2,3: 4x5', found {} numbers",
+        ints.len()
+      )));
     }
-    Claim {
-      id: ints[0] as u32,
-      x: ints[1] as u32,
-      y: ints[2] as u32,
-      w: ints[3] as u32,
-      h: ints[4] as u32,
+    if ints[1].checked_add(ints[3]).is_none() || ints[2].checked_add(ints[4]).is_none() {
+      return Err(ParseError::new(format!("claim extends past {}", u32::MAX)));
     }
+    Ok(Claim {
+      id: ints[0],
+      pos: Point2::new(ints[1], ints[2]),
+      size: Point2::new(ints[3], ints[4]),
+    })
   }
-  fn xmax(&self) -> u32 {
-    self.x + self.w
+  /// The bottom-right corner, exclusive.
+  pub fn max(&self) -> Point2 {
+    self.pos + self.size
   }
-  fn ymax(&self) -> u32 {
-    self.y + self.h
+  pub fn xmax(&self) -> u32 {
+    self.max().x
   }
-  fn overlaps(&self, other: &Claim) -> bool {
-    let xmin = self.x.max(other.x);
-    let ymin = self.y.max(other.y);
-    let xmax = self.xmax().min(other.xmax());
-    let ymax = self.ymax().min(other.ymax());
-
-    (xmin <= self.xmax() && xmin <= other.xmax())
-      && (ymin <= self.ymax() && ymin <= other.ymax())
-      && (xmax >= self.x && xmax >= other.x)
-      && (ymax >= self.y && ymax >= other.y)
-      && (xmin < xmax)
-      && (ymin < ymax)
+  pub fn ymax(&self) -> u32 {
+    self.max().y
+  }
+  pub fn overlaps(&self, other: &Claim) -> bool {
+    let (a, b) = (self.max(), other.max());
+    self.pos.x < b.x && other.pos.x < a.x && self.pos.y < b.y && other.pos.y < a.y
   }
 }
 
-pub fn run() {
-  let contents = include_str!("../data/day3.txt");
-  let claims = contents
-    .lines()
-    .map(|l| Claim::new(l))
-    .collect::<Vec<Claim>>();
-  println!("day 3 part 1: {}", part1(&claims));
-  println!("day 3 part 2: {}", part2(&claims).unwrap());
+pub struct Day3;
+
+impl Solution for Day3 {
+  type Input = Vec<Claim>;
+  type Part1 = usize;
+  type Part2 = u32;
+
+  fn parse(&self, contents: &str) -> Result<Vec<Claim>, ParseError> {
+    parse_lines(contents, Claim::new)
+  }
+
+  fn part1(&self, claims: &Vec<Claim>) -> Option<usize> {
+    Some(part1(claims))
+  }
+
+  fn part2(&self, claims: &Vec<Claim>) -> Option<u32> {
+    part2(claims)
+  }
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 enum ClaimCount {
   Zero,
   One,
   Intersect,
 }
 
+impl ClaimCount {
+  fn add(&mut self) {
+    match self {
+      ClaimCount::Zero => *self = ClaimCount::One,
+      ClaimCount::One => *self = ClaimCount::Intersect,
+      _ => {}
+    }
+  }
+}
+
+/// Above this many square inches in the claims' bounding box, only the
+/// claimed squares are tracked, in a map, rather than a grid of the lot.
+const MAX_GRID_CELLS: u64 = 1 << 24;
+
+/// Counts the square inches claimed more than once.
 fn part1(claims: &[Claim]) -> usize {
-  let mut grid = HashMap::<(u32, u32), ClaimCount>::new();
+  let claims: Vec<&Claim> = claims
+    .iter()
+    .filter(|c| c.size.x > 0 && c.size.y > 0)
+    .collect();
+  let squares = || {
+    claims.iter().flat_map(|c| {
+      (c.pos.y..c.ymax()).flat_map(move |y| (c.pos.x..c.xmax()).map(move |x| Point2::new(x, y)))
+    })
+  };
+  let (min, max) = match claims.first() {
+    Some(first) => claims
+      .iter()
+      .fold((first.pos, first.max()), |(min, max), c| {
+        (
+          Point2::new(min.x.min(c.pos.x), min.y.min(c.pos.y)),
+          Point2::new(max.x.max(c.xmax()), max.y.max(c.ymax())),
+        )
+      }),
+    None => return 0,
+  };
+  let cells = u64::from(max.x - min.x) * u64::from(max.y - min.y);
 
-  for claim in claims {
-    for x in (claim.x)..(claim.xmax()) {
-      for y in (claim.y)..(claim.ymax()) {
-        let entry = grid.entry((x, y)).or_insert(ClaimCount::Zero);
-        match entry {
-          ClaimCount::Zero => *entry = ClaimCount::One,
-          ClaimCount::One => *entry = ClaimCount::Intersect,
-          _ => {}
-        }
-      }
+  if cells <= MAX_GRID_CELLS {
+    let max = Point2::new(max.x - 1, max.y - 1);
+    let mut grid = Grid::from_fn(min, max, |_| ClaimCount::Zero).expect("grid is small");
+    for p in squares() {
+      grid[p].add();
+    }
+    grid
+      .values()
+      .filter(|v| **v == ClaimCount::Intersect)
+      .count()
+  } else {
+    let mut counts: HashMap<Point2, ClaimCount> = HashMap::new();
+    for p in squares() {
+      counts.entry(p).or_insert(ClaimCount::Zero).add();
     }
+    counts
+      .values()
+      .filter(|v| **v == ClaimCount::Intersect)
+      .count()
   }
-
-  grid
-    .values()
-    .filter(|v| **v == ClaimCount::Intersect)
-    .count()
 }
 
 fn part2(claims: &[Claim]) -> Option<u32> {
@@ -113,41 +162,69 @@ mod tests {
 #1 @ 1,3: 4x4
 #2 @ 3,1: 4x4
 #3 @ 5,5: 2x2";
-    let claims: Vec<Claim> = contents.lines().map(|l| Claim::new(l)).collect();
+    let claims = parse_lines(contents, Claim::new).unwrap();
     assert_eq!(part1(&claims), 4);
   }
 
   #[test]
+  fn far_from_the_origin() {
+    let contents = "\
+#1 @ 70000,70000: 2x2
+#2 @ 70001,70001: 2x2
+#3 @ 70010,65536: 1x1
+#4 @ 0,0: 0x0";
+    let claims = parse_lines(contents, Claim::new).unwrap();
+    assert_eq!(part1(&claims), 1);
+    assert!(Claim::new("#1 @ 4294967295,0: 2x2").is_err());
+  }
+
+  #[test]
+  fn widely_separated() {
+    let contents = "\
+#1 @ 0,0: 1x1
+#2 @ 3000000,3000000: 2x2
+#3 @ 3000001,3000001: 2x2";
+    let claims = parse_lines(contents, Claim::new).unwrap();
+    assert_eq!(part1(&claims), 1);
+    assert_eq!(part1(&claims[..2]), 0);
+  }
+
+  #[test]
+  #[allow(clippy::bool_assert_comparison)]
   fn overlaps() {
     let contents = "\
 #1 @ 1,3: 4x4
 #2 @ 3,1: 4x4
 #3 @ 5,5: 2x2";
-    let claims: Vec<Claim> = contents.lines().map(|l| Claim::new(l)).collect();
+    let claims = parse_lines(contents, Claim::new).unwrap();
     assert_eq!(claims[0].overlaps(&claims[1]), true);
     assert_eq!(claims[1].overlaps(&claims[2]), false);
   }
 
   #[test]
+  #[allow(clippy::bool_assert_comparison)]
   fn overlaps2() {
     let c1 = Claim {
       id: 0,
-      x: 0,
-      y: 0,
-      w: 2,
-      h: 2,
+      pos: Point2::new(0, 0),
+      size: Point2::new(2, 2),
     };
     let mut c2 = Claim {
       id: 0,
-      x: 2,
-      y: 2,
-      w: 2,
-      h: 2,
+      pos: Point2::new(2, 2),
+      size: Point2::new(2, 2),
     };
     assert_eq!(c1.overlaps(&c2), false);
-    c2.x = 1;
+    c2.pos.x = 1;
     assert_eq!(c1.overlaps(&c2), false);
-    c2.y = 1;
+    c2.pos.y = 1;
     assert_eq!(c1.overlaps(&c2), true);
   }
+
+  #[test]
+  fn malformed_claim() {
+    let err = Day3.parse("#1 @ 1,3: 4x4\n#2 @ 3,1 4x\n").unwrap_err();
+    assert_eq!(err.line, 2);
+    assert_eq!(err.text, "#2 @ 3,1 4x");
+  }
 }
@@ -1,49 +1,99 @@
+use crate::parse::{line_text, parse_lines, ParseError};
+use crate::solution::Solution;
 use crate::utils;
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 
-fn prepare_input(contents: &str) -> HashMap<u32, HashMap<u8, u32>> {
-  let mut entries: Vec<Entry> = contents.lines().map(|l| Entry::new(l)).collect();
-  entries.sort();
-  guard_to_minute_map(&entries)
+/// Guard id to minute of the midnight hour to how many nights they slept then.
+pub type GuardMinutes = HashMap<u32, HashMap<u8, u32>>;
+
+fn prepare_input(contents: &str) -> Result<GuardMinutes, ParseError> {
+  let mut entries: Vec<(usize, Entry)> = parse_lines(contents, Entry::new)?
+    .into_iter()
+    .enumerate()
+    .map(|(idx, entry)| (idx + 1, entry))
+    .collect();
+  entries.sort_by(|(_, a), (_, b)| a.time.cmp(&b.time));
+  guard_to_minute_map(&entries).map_err(|e| {
+    let line = e.line;
+    e.on_line(line, line_text(contents, line))
+  })
 }
 
-pub fn run() {
-  let contents = include_str!("../data/day4.txt");
-  let guard_id_to_minutes = prepare_input(contents);
-  println!("day 4 part 1: {}", part1(&guard_id_to_minutes));
-  println!("day 4 part 2: {}", part2(&guard_id_to_minutes));
+pub struct Day4;
+
+impl Solution for Day4 {
+  type Input = GuardMinutes;
+  type Part1 = u32;
+  type Part2 = u32;
+
+  fn parse(&self, contents: &str) -> Result<GuardMinutes, ParseError> {
+    prepare_input(contents)
+  }
+
+  fn part1(&self, guard_id_to_minutes: &GuardMinutes) -> Option<u32> {
+    Some(part1(guard_id_to_minutes))
+  }
+
+  fn part2(&self, guard_id_to_minutes: &GuardMinutes) -> Option<u32> {
+    Some(part2(guard_id_to_minutes))
+  }
 }
 
 #[derive(PartialEq, Eq, Debug, PartialOrd)]
-enum EntryKind {
+pub enum EntryKind {
   Guard(u32),
   Sleep,
   Wake,
 }
 
-#[derive(PartialEq, Eq, Debug, PartialOrd)]
-struct Entry {
-  time: Time,
-  kind: EntryKind,
+#[derive(PartialEq, Eq, Debug)]
+pub struct Entry {
+  pub time: Time,
+  pub kind: EntryKind,
 }
 
 impl Entry {
-  fn new(s: &str) -> Entry {
-    let ints = utils::scan_ints(s);
-    let year = ints[0] as u32;
-    let month = ints[1] as u8;
-    let day = ints[2] as u8;
-    let hours = ints[3] as u8;
-    let minutes = ints[4] as u8;
+  /// Parses a record like `[1518-11-01 00:05] falls asleep`.
+  pub fn new(s: &str) -> Result<Entry, ParseError> {
+    let ints: Vec<(usize, u32)> = utils::ints(s).with_offsets().collect::<Result<_, _>>()?;
+    if ints.len() < 5 {
+      return Err(ParseError::new(
+        "expected an entry like '[1518-11-01 00:00] falls asleep'",
+      ));
+    }
+    let field = |idx: usize, name: &str| {
+      let (offset, n) = ints[idx];
+      u8::try_from(n).map_err(|_| {
+        ParseError::new(format!("{} {} is out of range", name, n))
+          .at_column(s[..offset].chars().count() + 1)
+      })
+    };
+    let year = ints[0].1;
+    let month = field(1, "month")?;
+    let day = field(2, "day")?;
+    let hours = field(3, "hour")?;
+    let minutes = field(4, "minute")?;
     let kind = match ints.len() {
       5 => match (s.contains("falls asleep"), s.contains("wakes up")) {
         (true, false) => EntryKind::Sleep,
         (false, true) => EntryKind::Wake,
-        _ => panic!("unexpected value"),
+        _ => {
+          let column = s.find(']').map_or(1, |i| s[..=i].chars().count() + 1);
+          return Err(
+            ParseError::new("expected 'falls asleep', 'wakes up' or 'Guard #N begins shift'")
+              .at_column(column),
+          );
+        }
       },
-      6 => EntryKind::Guard(ints[5] as u32),
-      _ => panic!("error parsing"),
+      6 => EntryKind::Guard(ints[5].1),
+      n => {
+        return Err(ParseError::new(format!(
+          "expected a timestamp and at most one guard id, found {} numbers",
+          n
+        )))
+      }
     };
     let time = Time {
       year,
@@ -53,7 +103,7 @@ impl Entry {
       minutes,
     };
 
-    Entry { time, kind }
+    Ok(Entry { time, kind })
   }
 }
 
@@ -89,29 +139,23 @@ impl Iterator for TimeSpan {
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
-struct Time {
-  year: u32,
-  month: u8,
-  day: u8,
-  hours: u8,
-  minutes: u8,
+pub struct Time {
+  pub year: u32,
+  pub month: u8,
+  pub day: u8,
+  pub hours: u8,
+  pub minutes: u8,
 }
 
 impl Time {
   fn until(&self, o: &Self) -> Option<TimeSpan> {
-    match self.cmp(&o) {
+    match self.cmp(o) {
       Ordering::Greater => None,
       _ => Some(TimeSpan::from(self, o)),
     }
   }
 }
 
-impl Ord for Entry {
-  fn cmp(&self, o: &Self) -> Ordering {
-    self.time.cmp(&o.time)
-  }
-}
-
 impl Ord for Time {
   fn cmp(&self, o: &Self) -> Ordering {
     self
@@ -129,31 +173,44 @@ impl PartialOrd for Time {
   }
 }
 
-fn guard_to_minute_map(entries: &[Entry]) -> HashMap<u32, HashMap<u8, u32>> {
-  let mut guard_id_to_minutes: HashMap<u32, HashMap<u8, u32>> = HashMap::new();
+/// Takes entries in time order, each with the 1-based line it was read
+/// from. Errors carry that line but not its text, which the caller fills in
+/// from the input.
+fn guard_to_minute_map(entries: &[(usize, Entry)]) -> Result<GuardMinutes, ParseError> {
+  let mut guard_id_to_minutes: GuardMinutes = HashMap::new();
   let mut cur_guard_id: u32 = match entries.first() {
-    Some(Entry {
-      kind: EntryKind::Guard(guard_id),
-      ..
-    }) => *guard_id,
-    _ => panic!("First entry should be a guard"),
+    Some((
+      _,
+      Entry {
+        kind: EntryKind::Guard(guard_id),
+        ..
+      },
+    )) => *guard_id,
+    Some((line, _)) => {
+      return Err(
+        ParseError::new("the earliest entry should be a guard beginning a shift")
+          .on_line(*line, ""),
+      )
+    }
+    None => return Err(ParseError::new("no entries")),
   };
 
-  let mut cur_time = match entries.first() {
-    Some(Entry { time, .. }) => time,
-    _ => panic!("No minutes in first entry"),
-  };
+  let mut cur_time = &entries[0].1.time;
 
-  for entry in entries.iter().skip(1) {
+  for (line, entry) in entries.iter().skip(1) {
     match entry.kind {
       EntryKind::Guard(id) => cur_guard_id = id,
       EntryKind::Sleep => {
         cur_time = &entry.time;
       }
       EntryKind::Wake => {
-        let minutes_map = guard_id_to_minutes
-          .entry(cur_guard_id)
-          .or_insert_with(HashMap::new);
+        if cur_time.day != entry.time.day || cur_time.hours != entry.time.hours {
+          return Err(
+            ParseError::new("guard wakes up in a different hour than they fell asleep")
+              .on_line(*line, ""),
+          );
+        }
+        let minutes_map = guard_id_to_minutes.entry(cur_guard_id).or_default();
         for time in cur_time.until(&entry.time).unwrap() {
           let minutes_entry = minutes_map.entry(time.minutes).or_default();
           *minutes_entry += 1;
@@ -161,10 +218,13 @@ fn guard_to_minute_map(entries: &[Entry]) -> HashMap<u32, HashMap<u8, u32>> {
       }
     }
   }
-  guard_id_to_minutes
+  if guard_id_to_minutes.values().all(HashMap::is_empty) {
+    return Err(ParseError::new("no guard ever falls asleep"));
+  }
+  Ok(guard_id_to_minutes)
 }
 
-fn part1(guard_id_to_minutes: &HashMap<u32, HashMap<u8, u32>>) -> u32 {
+fn part1(guard_id_to_minutes: &GuardMinutes) -> u32 {
   let mut max_total = 0;
   let mut max_guard_id = 0;
   for (guard_id, minutes_map) in guard_id_to_minutes {
@@ -188,7 +248,7 @@ fn part1(guard_id_to_minutes: &HashMap<u32, HashMap<u8, u32>>) -> u32 {
   max_guard_id * u32::from(max_minute)
 }
 
-fn part2(guard_id_to_minutes: &HashMap<u32, HashMap<u8, u32>>) -> u32 {
+fn part2(guard_id_to_minutes: &GuardMinutes) -> u32 {
   let mut max_count = 0;
   let mut max_minute = 0;
   let mut max_guard_id = 0;
@@ -212,14 +272,41 @@ mod tests {
   #[test]
   fn example1() {
     let contents = include_str!("../data/day4-example.txt");
-    let map = prepare_input(contents);
+    let map = prepare_input(contents).unwrap();
     assert_eq!(part1(&map), 240);
   }
 
   #[test]
   fn example2() {
     let contents = include_str!("../data/day4-example.txt");
-    let map = prepare_input(contents);
+    let map = prepare_input(contents).unwrap();
     assert_eq!(part2(&map), 4455);
   }
+
+  #[test]
+  fn first_entry_not_a_guard() {
+    let contents = "\
+[1518-11-01 00:10] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:30] wakes up";
+    let err = prepare_input(contents).unwrap_err();
+    assert_eq!(err.line, 2);
+    assert_eq!(err.text, "[1518-11-01 00:05] falls asleep");
+  }
+
+  #[test]
+  fn field_out_of_range() {
+    let err = prepare_input("[1518-300-01 00:10] Guard #10 begins shift").unwrap_err();
+    assert_eq!((err.line, err.column), (1, 7));
+    assert_eq!(err.message, "month 300 is out of range");
+  }
+
+  #[test]
+  fn no_guard_sleeps() {
+    let contents = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-02 00:00] Guard #99 begins shift";
+    let err = prepare_input(contents).unwrap_err();
+    assert_eq!(err.message, "no guard ever falls asleep");
+  }
 }
@@ -1,16 +1,42 @@
-fn prepare_input(c: &str) -> &str {
-  c.trim()
+use crate::parse::ParseError;
+use crate::solution::Solution;
+
+fn prepare_input(c: &str) -> Result<&str, ParseError> {
+  let start = c.len() - c.trim_start().len();
+  let polymer = c.trim();
+  match polymer
+    .char_indices()
+    .find(|(_, ch)| !ch.is_ascii_alphabetic())
+  {
+    Some((idx, ch)) => {
+      Err(ParseError::new(format!("unexpected unit {:?}", ch)).at_offset(c, start + idx))
+    }
+    None => Ok(polymer),
+  }
 }
 
-pub fn run() {
-  let contents = include_str!("../data/day5.txt");
-  let contents = prepare_input(contents);
-  println!("day 5 part 1: {}", part1(contents));
-  println!("day 5 part 2: {}", part2(contents));
+pub struct Day5;
+
+impl Solution for Day5 {
+  type Input = String;
+  type Part1 = usize;
+  type Part2 = usize;
+
+  fn parse(&self, contents: &str) -> Result<String, ParseError> {
+    prepare_input(contents).map(String::from)
+  }
+
+  fn part1(&self, contents: &String) -> Option<usize> {
+    Some(part1(contents))
+  }
+
+  fn part2(&self, contents: &String) -> Option<usize> {
+    Some(part2(contents))
+  }
 }
 
 fn reactive(lhs: char, rhs: char) -> bool {
-  lhs.to_ascii_lowercase() == rhs.to_ascii_lowercase() && (lhs as i8 - rhs as i8).abs() == 0x20
+  lhs.eq_ignore_ascii_case(&rhs) && (lhs as i8 - rhs as i8).abs() == 0x20
 }
 
 fn part1(contents: &str) -> usize {
@@ -41,7 +67,7 @@ fn part2(contents: &str) -> usize {
   // that don't contain enough to beat the current `min`
   for c in b'a'..=b'z' {
     let c = c as char;
-    let test = contents.replace(c, "").replace(c.to_ascii_uppercase(), "");
+    let test = contents.replace([c, c.to_ascii_uppercase()], "");
     let len = part1(&test);
     if len < min {
       min = len;
@@ -55,6 +81,7 @@ mod tests {
   use super::*;
 
   #[test]
+  #[allow(clippy::bool_assert_comparison)]
   fn test_react() {
     assert!(reactive('a', 'A'));
     assert!(reactive('A', 'a'));
@@ -65,7 +92,13 @@ mod tests {
 
   #[test]
   fn example1() {
-    let contents = prepare_input("dabAcCaCBAcCcaDA");
+    let contents = prepare_input("dabAcCaCBAcCcaDA").unwrap();
     assert_eq!(part1(contents), 10);
   }
+
+  #[test]
+  fn bad_unit() {
+    let err = prepare_input("  dabAc1aCBA\n").unwrap_err();
+    assert_eq!((err.line, err.column), (1, 8));
+  }
 }
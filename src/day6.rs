@@ -1,64 +1,115 @@
-use crate::utils::{scan_ints, Point2};
+use crate::config::Params;
+use crate::parse::{parse_lines, ParseError};
+use crate::profile;
+use crate::solution::Solution;
+use crate::utils::{scan_ints, Grid, Point2};
 use std::collections::{HashMap, HashSet};
 
-fn prepare_input(s: &str) -> Vec<Point2> {
-  s.lines()
-    .map(|l| {
-      let ints = scan_ints(l);
-      Point2::new(ints[0] as u32, ints[1] as u32)
-    })
-    .collect()
+fn prepare_input(s: &str) -> Result<Vec<Point2>, ParseError> {
+  let points = parse_lines(s, |l| match scan_ints(l)?[..] {
+    [x, y] => Ok(Point2::new(x, y)),
+    ref ints => Err(ParseError::new(format!(
+      "expected a coordinate like '1, 6', found {} numbers",
+      ints.len()
+    ))),
+  })?;
+  if points.is_empty() {
+    return Err(ParseError::new("no coordinates"));
+  }
+  Ok(points)
+}
+
+pub struct Day6 {
+  /// Part 2 counts the locations whose total distance to every coordinate
+  /// is below this. The puzzle's example uses 32.
+  pub max_total_distance: u32,
+}
+
+impl Default for Day6 {
+  fn default() -> Day6 {
+    Day6 {
+      max_total_distance: 10000,
+    }
+  }
 }
 
-pub fn run() {
-  let contents = include_str!("../data/day6.txt");
-  let points = prepare_input(contents);
-  println!("day 6 part 1: {}", part1(&points));
-  println!("day 6 part 2: {}", part2(&points));
+impl Day6 {
+  pub fn from_params(mut params: Params) -> Result<Day6, String> {
+    let day = Day6 {
+      max_total_distance: params.get("max_total_distance", Day6::default().max_total_distance)?,
+    };
+    params.finish()?;
+    Ok(day)
+  }
 }
 
-fn get_bounds(points: &[Point2]) -> (u32, u32, u32, u32) {
+impl Solution for Day6 {
+  type Input = Vec<Point2>;
+  type Part1 = u32;
+  type Part2 = u32;
+
+  fn parse(&self, contents: &str) -> Result<Vec<Point2>, ParseError> {
+    prepare_input(contents)
+  }
+
+  fn part1(&self, points: &Vec<Point2>) -> Option<u32> {
+    part1(points)
+  }
+
+  fn part2(&self, points: &Vec<Point2>) -> Option<u32> {
+    part2(points, self.max_total_distance)
+  }
+}
+
+fn get_bounds(points: &[Point2]) -> (Point2, Point2) {
   let xmin = points.iter().min_by_key(|p| p.x).unwrap().x;
   let xmax = points.iter().max_by_key(|p| p.x).unwrap().x;
   let ymin = points.iter().min_by_key(|p| p.y).unwrap().y;
   let ymax = points.iter().max_by_key(|p| p.y).unwrap().y;
-  (xmin, xmax, ymin, ymax)
+  (Point2::new(xmin, ymin), Point2::new(xmax, ymax))
 }
 
-fn part1(points: &[Point2]) -> u32 {
-  let (xmin, xmax, ymin, ymax) = get_bounds(points);
+/// Index of the point closest to `cur`, or `None` if several are equally close.
+fn closest_point(cur: Point2, points: &[Point2]) -> Option<usize> {
+  let mut min_dist = u32::MAX;
+  let mut closest_point_idx = 0;
+  let mut is_tie = false;
+  for (idx, point) in points.iter().enumerate() {
+    let dist = cur.manhattan_dist(point);
+    if dist < min_dist {
+      is_tie = false;
+      min_dist = dist;
+      closest_point_idx = idx;
+    } else if dist == min_dist {
+      is_tie = true;
+    }
+  }
+  if is_tie {
+    None
+  } else {
+    Some(closest_point_idx)
+  }
+}
+
+/// `None` if the points are spread too far apart to fit in a grid, here and
+/// in part 2.
+fn part1(points: &[Point2]) -> Option<u32> {
+  let (min, max) = get_bounds(points);
+  let owners = profile::span("owners", || {
+    Grid::from_fn(min, max, |p| closest_point(p, points))
+  })
+  .ok()?;
 
   // keep track of total owned squares for each point
   // keep track of which points' extents touch the grid edge, to disregard them
   let mut points_touch_edge = HashSet::<usize>::new();
   let mut owned_squares_per_point = HashMap::<usize, u32>::new();
 
-  let is_boundary = |x: u32, y: u32| -> bool { x == xmin || x == xmax || y == ymin || y == ymax };
-
-  for x in xmin..=xmax {
-    for y in ymin..=ymax {
-      let mut min_dist = u32::max_value();
-      let mut closest_point_idx = 0;
-      let mut is_tie = false;
-      let cur_point = Point2::new(x, y);
-      for (idx, point) in points.iter().enumerate() {
-        let dist = cur_point.manhattan_dist(point);
-        if dist < min_dist {
-          is_tie = false;
-          min_dist = dist;
-          closest_point_idx = idx;
-        } else if dist == min_dist {
-          is_tie = true;
-        }
-      }
-      if !is_tie {
-        let entry = owned_squares_per_point
-          .entry(closest_point_idx)
-          .or_default();
-        *entry += 1;
-        if is_boundary(x, y) {
-          points_touch_edge.insert(closest_point_idx);
-        }
+  for (p, owner) in owners.iter() {
+    if let Some(idx) = *owner {
+      *owned_squares_per_point.entry(idx).or_default() += 1;
+      if owners.is_edge(p) {
+        points_touch_edge.insert(idx);
       }
     }
   }
@@ -73,29 +124,48 @@ fn part1(points: &[Point2]) -> u32 {
     }
   }
 
-  max_area
+  Some(max_area)
 }
 
-fn total_distance(x: u32, y: u32, points: &[Point2]) -> u32 {
+fn total_distance(cur: Point2, points: &[Point2]) -> u32 {
   let mut total = 0;
-  let cur = Point2::new(x, y);
   for point in points {
     total += cur.manhattan_dist(point);
   }
   total
 }
 
-fn part2(points: &[Point2]) -> u32 {
-  const MIN_REGION_DIST: u32 = 10000;
-  let (xmin, xmax, ymin, ymax) = get_bounds(points);
-  let mut sum = 0;
-  for x in xmin..=xmax {
-    for y in ymin..=ymax {
-      let dist = total_distance(x, y, points);
-      if dist < MIN_REGION_DIST {
-        sum += 1;
-      }
-    }
+fn part2(points: &[Point2], max_total_distance: u32) -> Option<u32> {
+  let (min, max) = get_bounds(points);
+  let distances = profile::span("distances", || {
+    Grid::from_fn(min, max, |p| total_distance(p, points))
+  })
+  .ok()?;
+  Some(
+    distances
+      .values()
+      .filter(|&&dist| dist < max_total_distance)
+      .count() as u32,
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn example() {
+    let day = Day6 {
+      max_total_distance: 32,
+    };
+    let points = Solution::parse(&day, "1, 1\n1, 6\n8, 3\n3, 4\n5, 5\n8, 9\n").unwrap();
+    assert_eq!(day.part1(&points), Some(17));
+    assert_eq!(day.part2(&points), Some(16));
+  }
+
+  #[test]
+  fn no_coordinates() {
+    let err = prepare_input("").unwrap_err();
+    assert_eq!(err.message, "no coordinates");
   }
-  sum
 }
@@ -1,35 +1,89 @@
+use crate::config::Params;
+use crate::parse::{parse_lines, ParseError};
+use crate::solution::Solution;
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
-use std::fs;
 
 #[derive(Debug)]
-struct Dependency {
-  step: char,
-  dep: char,
+pub struct Dependency {
+  pub step: char,
+  /// Must be finished before `step` can begin.
+  pub dep: char,
 }
 
-type DependencyMap = HashMap<char, HashSet<char>>;
+/// Step to the steps that must be finished before it.
+pub type DependencyMap = HashMap<char, HashSet<char>>;
 
-pub fn run() {
-  let contents = fs::read_to_string("./data/day7.txt").unwrap();
-  let deps: Vec<Dependency> = contents.lines().map(|l| parse_line(l)).collect();
-  let mut dep_map: DependencyMap = HashMap::new();
-  for dep in deps {
-    dep_map.entry(dep.step).or_default().insert(dep.dep);
-    dep_map.entry(dep.dep).or_default();
+pub struct Day7 {
+  /// Workers available in part 2, you included.
+  pub workers: usize,
+  /// Seconds every step takes on top of its letter (A = 1, B = 2, ...).
+  /// The puzzle's example uses 2 workers and a base time of 0.
+  pub base_time: u32,
+}
+
+impl Default for Day7 {
+  fn default() -> Day7 {
+    Day7 {
+      workers: 5,
+      base_time: 60,
+    }
+  }
+}
+
+impl Day7 {
+  pub fn from_params(mut params: Params) -> Result<Day7, String> {
+    let default = Day7::default();
+    let day = Day7 {
+      workers: params.get("workers", default.workers)?,
+      base_time: params.get("base_time", default.base_time)?,
+    };
+    params.finish()?;
+    if day.workers == 0 {
+      return Err("day7.workers must be at least 1".to_string());
+    }
+    Ok(day)
+  }
+}
+
+impl Solution for Day7 {
+  type Input = DependencyMap;
+  type Part1 = String;
+  type Part2 = u32;
+
+  fn parse(&self, contents: &str) -> Result<DependencyMap, ParseError> {
+    let deps = parse_lines(contents, parse_line)?;
+    let mut dep_map: DependencyMap = HashMap::new();
+    for dep in deps {
+      dep_map.entry(dep.step).or_default().insert(dep.dep);
+      dep_map.entry(dep.dep).or_default();
+    }
+    if dep_map.is_empty() {
+      return Err(ParseError::new("no steps"));
+    }
+    Ok(dep_map)
+  }
+
+  fn part1(&self, dep_map: &DependencyMap) -> Option<String> {
+    Some(part1(dep_map))
+  }
+
+  fn part2(&self, dep_map: &DependencyMap) -> Option<u32> {
+    Some(part2(dep_map, self.workers, self.base_time))
   }
-  println!("day 7 part 1: {}", part1(&dep_map));
-  println!("day 7 part 2: {:?}", part2(&dep_map));
 }
 
-fn parse_line(l: &str) -> Dependency {
+/// Parses `Step C must be finished before step A can begin.`
+pub fn parse_line(l: &str) -> Result<Dependency, ParseError> {
   let re = Regex::new(r"Step (.) must .* before step (.)").unwrap();
-  let caps = re.captures(l).unwrap();
+  let caps = re.captures(l).ok_or_else(|| {
+    ParseError::new("expected 'Step A must be finished before step B can begin.'")
+  })?;
 
-  Dependency {
+  Ok(Dependency {
     dep: caps.get(1).unwrap().as_str().chars().next().unwrap(),
     step: caps.get(2).unwrap().as_str().chars().next().unwrap(),
-  }
+  })
 }
 
 fn part1(dep_map: &DependencyMap) -> String {
@@ -59,20 +113,22 @@ fn part1(dep_map: &DependencyMap) -> String {
 }
 
 struct Worker {
-  elapsed: u8,
+  elapsed: u32,
   cur: Option<char>,
+  base_time: u32,
 }
 
 impl Worker {
-  fn new() -> Worker {
+  fn new(base_time: u32) -> Worker {
     Worker {
       elapsed: 0,
       cur: None,
+      base_time,
     }
   }
 
-  fn work_time(c: char) -> u8 {
-    60 + 1 + (c as u8) - b'A'
+  fn work_time(&self, c: char) -> u32 {
+    self.base_time + 1 + (c as u32) - ('A' as u32)
   }
 
   fn tick(&mut self) {
@@ -81,7 +137,7 @@ impl Worker {
 
   fn can_complete(&self) -> bool {
     if let Some(cur) = self.cur {
-      let needed = Worker::work_time(cur);
+      let needed = self.work_time(cur);
       self.elapsed >= needed
     } else {
       false
@@ -99,11 +155,11 @@ impl Worker {
   }
 
   fn can_work(&self) -> bool {
-    self.cur == None
+    self.cur.is_none()
   }
 
   fn is_working(&self) -> bool {
-    self.cur != None
+    self.cur.is_some()
   }
 
   fn work(&mut self, c: char) {
@@ -118,10 +174,10 @@ struct Pool {
 }
 
 impl Pool {
-  fn new(count: usize) -> Pool {
+  fn new(count: usize, base_time: u32) -> Pool {
     let mut workers = vec![];
     for _ in 0..count {
-      workers.push(Worker::new());
+      workers.push(Worker::new(base_time));
     }
     Pool {
       elapsed: 0,
@@ -167,12 +223,12 @@ impl Pool {
   }
 }
 
-fn part2(dep_map: &DependencyMap) -> u32 {
+fn part2(dep_map: &DependencyMap, workers: usize, base_time: u32) -> u32 {
   let mut seen: HashSet<char> = HashSet::new();
   let mut completed: HashSet<char> = HashSet::new();
   let mut next: Vec<char> = vec![];
 
-  let mut pool = Pool::new(5);
+  let mut pool = Pool::new(workers, base_time);
   while completed.len() < dep_map.len() {
     next.sort();
     while pool.can_work() && !next.is_empty() {
@@ -197,3 +253,35 @@ fn part2(dep_map: &DependencyMap) -> u32 {
   }
   pool.elapsed - 1
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const EXAMPLE: &str = "\
+Step C must be finished before step A can begin.
+Step C must be finished before step F can begin.
+Step A must be finished before step B can begin.
+Step A must be finished before step D can begin.
+Step B must be finished before step E can begin.
+Step D must be finished before step E can begin.
+Step F must be finished before step E can begin.
+";
+
+  #[test]
+  fn example() {
+    let day = Day7 {
+      workers: 2,
+      base_time: 0,
+    };
+    let dep_map = Solution::parse(&day, EXAMPLE).unwrap();
+    assert_eq!(day.part1(&dep_map), Some("CABDFE".to_string()));
+    assert_eq!(day.part2(&dep_map), Some(15));
+  }
+
+  #[test]
+  fn no_steps() {
+    let err = Solution::parse(&Day7::default(), "").unwrap_err();
+    assert_eq!(err.message, "no steps");
+  }
+}
@@ -0,0 +1,326 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Environment variable that overrides where day inputs are looked up.
+pub const DATA_DIR_ENV: &str = "AOC_DATA_DIR";
+
+/// Where a day's puzzle input comes from.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Source {
+  /// `dayN.txt` in the data directory.
+  Day(u8),
+  File(PathBuf),
+  Stdin,
+}
+
+impl Source {
+  /// `-` means stdin, anything else is a file path.
+  pub fn from_arg(arg: &str) -> Source {
+    if arg == "-" {
+      Source::Stdin
+    } else {
+      Source::File(PathBuf::from(arg))
+    }
+  }
+}
+
+#[derive(Debug)]
+pub enum InputError {
+  /// No `dayN.txt` in the data directory.
+  Missing(u8, PathBuf),
+  Io(PathBuf, io::Error),
+  Stdin(io::Error),
+}
+
+impl fmt::Display for InputError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      InputError::Missing(day, path) => write!(
+        f,
+        "no input for day {}: {} does not exist (set {} or pass --data-dir / --input)",
+        day,
+        path.display(),
+        DATA_DIR_ENV
+      ),
+      InputError::Io(path, e) => write!(f, "could not read {}: {}", path.display(), e),
+      InputError::Stdin(e) => write!(f, "could not read stdin: {}", e),
+    }
+  }
+}
+
+impl std::error::Error for InputError {}
+
+/// Resolves and reads puzzle inputs. The data directory is, in order of
+/// preference: an explicit override, `$AOC_DATA_DIR`, `./data`, and finally
+/// the `data` directory of this crate's checkout.
+pub struct Loader {
+  data_dir: PathBuf,
+}
+
+impl Loader {
+  pub fn new(data_dir: Option<PathBuf>) -> Loader {
+    let data_dir = data_dir
+      .or_else(|| env::var_os(DATA_DIR_ENV).map(PathBuf::from))
+      .unwrap_or_else(|| {
+        let local = PathBuf::from("data");
+        if local.is_dir() {
+          local
+        } else {
+          Path::new(env!("CARGO_MANIFEST_DIR")).join("data")
+        }
+      });
+    Loader { data_dir }
+  }
+
+  pub fn day_path(&self, day: u8) -> PathBuf {
+    self.data_dir.join(format!("day{}.txt", day))
+  }
+
+  /// The file `source` reads from, if it is a file.
+  pub fn path(&self, source: &Source) -> Option<PathBuf> {
+    match source {
+      Source::Day(day) => Some(self.day_path(*day)),
+      Source::File(path) => Some(path.clone()),
+      Source::Stdin => None,
+    }
+  }
+
+  /// How `source` is named in diagnostics.
+  pub fn describe(&self, source: &Source) -> String {
+    match self.path(source) {
+      Some(path) => path.display().to_string(),
+      None => "<stdin>".to_string(),
+    }
+  }
+
+  pub fn load(&self, source: &Source) -> Result<String, InputError> {
+    match source {
+      Source::Day(day) => {
+        let path = self.day_path(*day);
+        read_file(&path).map_err(|e| match e {
+          InputError::Io(_, ref io) if io.kind() == io::ErrorKind::NotFound => {
+            InputError::Missing(*day, path)
+          }
+          e => e,
+        })
+      }
+      Source::File(path) => read_file(path),
+      Source::Stdin => {
+        let mut contents = String::new();
+        io::stdin()
+          .read_to_string(&mut contents)
+          .map_err(InputError::Stdin)?;
+        Ok(contents)
+      }
+    }
+  }
+}
+
+/// What `normalize` had to change. Empty for clean input.
+#[derive(Debug, Default, PartialEq)]
+pub struct Normalization {
+  pub bom: bool,
+  /// Lines that ended in `\r\n`.
+  pub crlf: usize,
+  /// Lines that had whitespace at the end (CRs aside).
+  pub trailing_whitespace: usize,
+  /// Blank lines dropped from the end.
+  pub blank_lines: usize,
+}
+
+impl Normalization {
+  pub fn is_empty(&self) -> bool {
+    *self == Normalization::default()
+  }
+}
+
+impl fmt::Display for Normalization {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut changes = vec![];
+    if self.bom {
+      changes.push("removed the byte order mark".to_string());
+    }
+    if self.crlf > 0 {
+      changes.push(format!("converted {} CRLF line ending(s)", self.crlf));
+    }
+    if self.trailing_whitespace > 0 {
+      changes.push(format!(
+        "trimmed trailing whitespace from {} line(s)",
+        self.trailing_whitespace
+      ));
+    }
+    if self.blank_lines > 0 {
+      changes.push(format!(
+        "dropped {} trailing blank line(s)",
+        self.blank_lines
+      ));
+    }
+    if changes.is_empty() {
+      write!(f, "no changes")
+    } else {
+      write!(f, "{}", changes.join(", "))
+    }
+  }
+}
+
+/// Cleans up input the way every day's parser expects it: no byte order
+/// mark, `\n` line endings, no whitespace at the end of a line and no blank
+/// lines at the end, with each line (the last included) ending in `\n`.
+/// Leading whitespace and blank lines between sections are kept.
+pub fn normalize(contents: &str) -> (String, Normalization) {
+  let mut changes = Normalization::default();
+  let contents = match contents.strip_prefix('\u{feff}') {
+    Some(rest) => {
+      changes.bom = true;
+      rest
+    }
+    None => contents,
+  };
+  let mut out = String::with_capacity(contents.len());
+  let mut blank = 0;
+  for line in contents.split_inclusive('\n') {
+    let mut line = line.strip_suffix('\n').unwrap_or(line);
+    if let Some(rest) = line.strip_suffix('\r') {
+      changes.crlf += 1;
+      line = rest;
+    }
+    let trimmed = line.trim_end();
+    if trimmed.len() != line.len() {
+      changes.trailing_whitespace += 1;
+    }
+    if trimmed.is_empty() {
+      // only written out if something follows
+      blank += 1;
+      continue;
+    }
+    for _ in 0..blank {
+      out.push('\n');
+    }
+    blank = 0;
+    out.push_str(trimmed);
+    out.push('\n');
+  }
+  changes.blank_lines = blank;
+  (out, changes)
+}
+
+/// A short, stable fingerprint of an input (64-bit FNV-1a, in hex), so
+/// reported results can be matched to the input they came from.
+pub fn hash(contents: &str) -> String {
+  let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+  for byte in contents.bytes() {
+    hash ^= u64::from(byte);
+    hash = hash.wrapping_mul(0x0100_0000_01b3);
+  }
+  format!("{:016x}", hash)
+}
+
+/// Every input file in `dir`, sorted by name: regular files other than
+/// `.answers` files and hidden files.
+pub fn inputs_in(dir: &Path) -> Result<Vec<PathBuf>, InputError> {
+  let io_err = |e| InputError::Io(dir.to_path_buf(), e);
+  let mut inputs = vec![];
+  for entry in fs::read_dir(dir).map_err(io_err)? {
+    let path = entry.map_err(io_err)?.path();
+    let hidden = path
+      .file_name()
+      .is_some_and(|n| n.to_string_lossy().starts_with('.'));
+    let answers = path.extension().is_some_and(|e| e == "answers");
+    if path.is_file() && !hidden && !answers {
+      inputs.push(path);
+    }
+  }
+  inputs.sort();
+  Ok(inputs)
+}
+
+fn read_file(path: &Path) -> Result<String, InputError> {
+  fs::read_to_string(path).map_err(|e| InputError::Io(path.to_path_buf(), e))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn explicit_data_dir() {
+    let loader = Loader::new(Some(PathBuf::from("/tmp/aoc")));
+    assert_eq!(loader.day_path(3), PathBuf::from("/tmp/aoc/day3.txt"));
+  }
+
+  #[test]
+  fn loads_bundled_day() {
+    let loader = Loader::new(Some(Path::new(env!("CARGO_MANIFEST_DIR")).join("data")));
+    let contents = loader.load(&Source::Day(5)).unwrap();
+    assert!(!contents.trim().is_empty());
+  }
+
+  #[test]
+  fn missing_input() {
+    let loader = Loader::new(Some(PathBuf::from("/nonexistent/aoc")));
+    match loader.load(&Source::Day(1)) {
+      Err(InputError::Missing(1, path)) => {
+        assert_eq!(path, PathBuf::from("/nonexistent/aoc/day1.txt"))
+      }
+      other => panic!("expected a missing input, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn inputs_in_directory() {
+    let data = Path::new(env!("CARGO_MANIFEST_DIR")).join("data");
+    let inputs = inputs_in(&data).unwrap();
+    assert!(inputs.contains(&data.join("day4-example.txt")));
+    assert!(inputs.windows(2).all(|w| w[0] < w[1]));
+    assert!(inputs.iter().all(|p| p.extension().unwrap() == "txt"));
+    assert!(inputs_in(Path::new("/nonexistent/aoc")).is_err());
+  }
+
+  #[test]
+  fn normalizes_windows_input() {
+    let (text, changes) = normalize("\u{feff}abc\r\ndef  \r\n\r\n\tghi\r\n \r\n\r\n");
+    assert_eq!(text, "abc\ndef\n\n\tghi\n");
+    assert_eq!(
+      changes,
+      Normalization {
+        bom: true,
+        crlf: 6,
+        trailing_whitespace: 2,
+        blank_lines: 2,
+      }
+    );
+    assert_eq!(
+      changes.to_string(),
+      "removed the byte order mark, converted 6 CRLF line ending(s), \
+       trimmed trailing whitespace from 2 line(s), dropped 2 trailing blank line(s)"
+    );
+  }
+
+  #[test]
+  fn clean_input_is_unchanged() {
+    let (text, changes) = normalize("+1\n-2\n");
+    assert_eq!(text, "+1\n-2\n");
+    assert!(changes.is_empty());
+    // a missing final newline is added without comment
+    assert_eq!(normalize("x").0, "x\n");
+    assert_eq!(normalize("").0, "");
+  }
+
+  #[test]
+  fn fnv_hash() {
+    assert_eq!(hash(""), "cbf29ce484222325");
+    assert_eq!(hash("a"), "af63dc4c8601ec8c");
+  }
+
+  #[test]
+  fn source_from_arg() {
+    assert_eq!(Source::from_arg("-"), Source::Stdin);
+    assert_eq!(
+      Source::from_arg("in.txt"),
+      Source::File(PathBuf::from("in.txt"))
+    );
+  }
+}
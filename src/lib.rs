@@ -0,0 +1,28 @@
+//! Advent of Code 2018 solutions. Each `dayN` module implements
+//! `solution::Solution`, and `solution::Registry` lists them all; the rest of
+//! the crate loads inputs, runs, benchmarks and checks them for the `aoc`
+//! binary.
+
+pub mod answers;
+pub mod baseline;
+pub mod bench;
+pub mod cli;
+pub mod commands;
+pub mod config;
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day4;
+pub mod day5;
+pub mod day6;
+pub mod day7;
+pub mod input;
+pub mod memory;
+pub mod output;
+pub mod parse;
+pub mod pool;
+pub mod profile;
+pub mod scaffold;
+pub mod solution;
+pub mod timeout;
+pub mod utils;
@@ -1,22 +1,42 @@
-mod day1;
-mod day2;
-mod day3;
-mod day4;
-mod day5;
-mod day6;
-mod day7;
-mod utils;
-
-use utils::time;
+use advent_of_code_2018_rust::cli::{self, Command, RunArgs};
+use advent_of_code_2018_rust::commands;
+use advent_of_code_2018_rust::scaffold;
+use advent_of_code_2018_rust::solution::Registry;
+use std::{env, process};
 
 fn main() {
-  time("Advent of Code Problems", || {
-    time("day1", day1::run);
-    time("day2", day2::run);
-    time("day3", day3::run);
-    time("day4", day4::run);
-    time("day5", day5::run);
-    time("day6", day6::run);
-    time("day7", day7::run);
-  });
+  let command = match cli::parse(env::args().skip(1)) {
+    Ok(command) => command,
+    Err(e) => {
+      eprintln!("error: {}\n\n{}", e, cli::USAGE);
+      process::exit(2);
+    }
+  };
+
+  let registry = |args: &RunArgs| Registry::with_config(&args.load_config()?);
+  let result = match command {
+    Command::Run(args) => registry(&args).and_then(|r| commands::run(&r, &args)),
+    Command::Bench(args) => registry(&args.run).and_then(|r| commands::bench(&r, &args)),
+    Command::Verify(args) => registry(&args.run).and_then(|r| commands::verify(&r, &args)),
+    Command::Batch(args) => registry(&args.run).and_then(|r| commands::batch(&r, &args)),
+    Command::NewDay(day) => scaffold::new_day(&scaffold::default_root(), day).map(|paths| {
+      for path in paths {
+        println!("wrote {}", path.display());
+      }
+    }),
+    Command::List => {
+      for entry in Registry::new().iter() {
+        println!("day {}", entry.day);
+      }
+      Ok(())
+    }
+    Command::Help => {
+      println!("{}", cli::USAGE);
+      Ok(())
+    }
+  };
+  if let Err(e) = result {
+    eprintln!("error: {}", e);
+    process::exit(1);
+  }
 }
@@ -0,0 +1,158 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+
+/// Wraps the system allocator and counts what each thread allocates. It is
+/// only installed with the `alloc-stats` feature:
+///
+/// ```text
+/// cargo run --release --features alloc-stats
+/// ```
+pub struct Counting;
+
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+thread_local! {
+  // per thread so days run with --jobs don't count each other's work
+  static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+  static BYTES: Cell<u64> = const { Cell::new(0) };
+  // signed, since a thread can free memory another thread allocated
+  static LIVE: Cell<i64> = const { Cell::new(0) };
+  static PEAK: Cell<i64> = const { Cell::new(0) };
+}
+
+fn count(allocated: usize, freed: usize) {
+  // try_with: the allocator still runs while thread locals are torn down
+  let _ = LIVE.try_with(|live| {
+    let now = live.get() + allocated as i64 - freed as i64;
+    live.set(now);
+    let _ = PEAK.try_with(|peak| peak.set(peak.get().max(now)));
+  });
+  if allocated > 0 {
+    let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+    let _ = BYTES.try_with(|n| n.set(n.get() + allocated as u64));
+  }
+}
+
+unsafe impl GlobalAlloc for Counting {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    let ptr = System.alloc(layout);
+    if !ptr.is_null() {
+      count(layout.size(), 0);
+    }
+    ptr
+  }
+
+  unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+    let ptr = System.alloc_zeroed(layout);
+    if !ptr.is_null() {
+      count(layout.size(), 0);
+    }
+    ptr
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    System.dealloc(ptr, layout);
+    count(0, layout.size());
+  }
+
+  /// Counts as a new allocation of the full new size.
+  unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    let new = System.realloc(ptr, layout, new_size);
+    if !new.is_null() {
+      count(new_size, layout.size());
+    }
+    new
+  }
+}
+
+/// Whether the counting allocator is installed.
+pub fn enabled() -> bool {
+  cfg!(feature = "alloc-stats")
+}
+
+/// What a piece of work allocated on the calling thread.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AllocStats {
+  pub allocations: u64,
+  pub bytes: u64,
+  /// Most memory held at once, above what was live before the work started.
+  pub peak: u64,
+}
+
+/// Runs `f` and returns its result with what it allocated. Everything reads
+/// zero unless the `alloc-stats` feature is on.
+pub fn measure<T, F: FnOnce() -> T>(f: F) -> (T, AllocStats) {
+  let (allocations, bytes) = (ALLOCATIONS.with(Cell::get), BYTES.with(Cell::get));
+  let live = LIVE.with(Cell::get);
+  let outer_peak = PEAK.with(|peak| peak.replace(live));
+  let result = f();
+  let peak = PEAK.with(|peak| peak.replace(peak.get().max(outer_peak)));
+  let stats = AllocStats {
+    allocations: ALLOCATIONS.with(Cell::get) - allocations,
+    bytes: BYTES.with(Cell::get) - bytes,
+    peak: (peak - live).max(0) as u64,
+  };
+  (result, stats)
+}
+
+/// Byte counts in the largest unit that keeps them above 1.
+fn bytes(n: u64) -> String {
+  const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+  let mut value = n as f64;
+  let mut unit = 0;
+  while value >= 1024.0 && unit < UNITS.len() - 1 {
+    value /= 1024.0;
+    unit += 1;
+  }
+  match unit {
+    0 => format!("{} B", n),
+    _ => format!("{:.1} {}", value, UNITS[unit]),
+  }
+}
+
+impl fmt::Display for AllocStats {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(
+      f,
+      "{} allocs, {} allocated, {} peak",
+      self.allocations,
+      bytes(self.bytes),
+      bytes(self.peak)
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn display() {
+    let stats = AllocStats {
+      allocations: 3,
+      bytes: 5 * 1024 * 1024 + 1,
+      peak: 100,
+    };
+    assert_eq!(stats.to_string(), "3 allocs, 5.0 MiB allocated, 100 B peak");
+  }
+
+  #[test]
+  fn counts_when_enabled() {
+    let (v, stats) = measure(|| {
+      let big = vec![0u8; 4096];
+      drop(big);
+      vec![1u64; 8]
+    });
+    assert_eq!(v.len(), 8);
+    if enabled() {
+      assert_eq!(stats.allocations, 2);
+      assert_eq!(stats.bytes, 4096 + 64);
+      assert_eq!(stats.peak, 4096);
+    } else {
+      assert_eq!(stats, AllocStats::default());
+    }
+  }
+}
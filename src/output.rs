@@ -0,0 +1,213 @@
+use std::fmt::Write;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How results are reported: the human-readable default, one JSON object
+/// per line, or CSV with a header row.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Format {
+  #[default]
+  Text,
+  Json,
+  Csv,
+}
+
+impl FromStr for Format {
+  type Err = String;
+  fn from_str(s: &str) -> Result<Format, String> {
+    match s {
+      "text" => Ok(Format::Text),
+      "json" => Ok(Format::Json),
+      "csv" => Ok(Format::Csv),
+      _ => Err(format!(
+        "unknown format '{}' (expected text, json or csv)",
+        s
+      )),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Null,
+  Bool(bool),
+  Int(u64),
+  Float(f64),
+  Str(String),
+}
+
+impl From<u8> for Value {
+  fn from(v: u8) -> Value {
+    Value::Int(u64::from(v))
+  }
+}
+
+impl From<u64> for Value {
+  fn from(v: u64) -> Value {
+    Value::Int(v)
+  }
+}
+
+impl From<usize> for Value {
+  fn from(v: usize) -> Value {
+    Value::Int(v as u64)
+  }
+}
+
+impl From<bool> for Value {
+  fn from(v: bool) -> Value {
+    Value::Bool(v)
+  }
+}
+
+impl From<f64> for Value {
+  fn from(v: f64) -> Value {
+    Value::Float(v)
+  }
+}
+
+/// Durations are reported in whole nanoseconds.
+impl From<Duration> for Value {
+  fn from(v: Duration) -> Value {
+    Value::Int(v.as_nanos() as u64)
+  }
+}
+
+impl From<&str> for Value {
+  fn from(v: &str) -> Value {
+    Value::Str(v.to_string())
+  }
+}
+
+impl From<String> for Value {
+  fn from(v: String) -> Value {
+    Value::Str(v)
+  }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+  fn from(v: Option<T>) -> Value {
+    v.map_or(Value::Null, Into::into)
+  }
+}
+
+pub type Record = Vec<(&'static str, Value)>;
+
+/// Writes records to stdout in a machine-readable format. CSV takes its
+/// header from the first record, so every record should have the same keys.
+pub struct Writer {
+  format: Format,
+  wrote_header: bool,
+}
+
+impl Writer {
+  pub fn new(format: Format) -> Writer {
+    Writer {
+      format,
+      wrote_header: false,
+    }
+  }
+
+  pub fn write(&mut self, record: &Record) {
+    match self.format {
+      Format::Text => {}
+      Format::Json => println!("{}", to_json(record)),
+      Format::Csv => {
+        if !self.wrote_header {
+          let keys: Vec<&str> = record.iter().map(|(k, _)| *k).collect();
+          println!("{}", keys.join(","));
+          self.wrote_header = true;
+        }
+        println!("{}", to_csv(record));
+      }
+    }
+  }
+}
+
+fn to_json(record: &Record) -> String {
+  let mut out = String::from("{");
+  for (idx, (key, value)) in record.iter().enumerate() {
+    if idx > 0 {
+      out.push(',');
+    }
+    write!(out, "{}:", json_string(key)).unwrap();
+    match value {
+      Value::Null => out.push_str("null"),
+      Value::Bool(b) => write!(out, "{}", b).unwrap(),
+      Value::Int(n) => write!(out, "{}", n).unwrap(),
+      Value::Float(f) if f.is_finite() => write!(out, "{}", f).unwrap(),
+      Value::Float(_) => out.push_str("null"),
+      Value::Str(s) => out.push_str(&json_string(s)),
+    }
+  }
+  out.push('}');
+  out
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+  let mut out = String::with_capacity(s.len() + 2);
+  out.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+fn to_csv(record: &Record) -> String {
+  let fields: Vec<String> = record
+    .iter()
+    .map(|(_, value)| match value {
+      Value::Null => String::new(),
+      Value::Bool(b) => b.to_string(),
+      Value::Int(n) => n.to_string(),
+      Value::Float(f) => f.to_string(),
+      Value::Str(s) if s.contains([',', '"', '\n', '\r']) => {
+        format!("\"{}\"", s.replace('"', "\"\""))
+      }
+      Value::Str(s) => s.clone(),
+    })
+    .collect();
+  fields.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn record() -> Record {
+    vec![
+      ("day", 7u8.into()),
+      ("answer", "a \"b\", c".into()),
+      ("duration_ns", Duration::from_micros(3).into()),
+      ("expected", None::<String>.into()),
+    ]
+  }
+
+  #[test]
+  fn json() {
+    assert_eq!(
+      to_json(&record()),
+      r#"{"day":7,"answer":"a \"b\", c","duration_ns":3000,"expected":null}"#
+    );
+  }
+
+  #[test]
+  fn csv() {
+    assert_eq!(to_csv(&record()), r#"7,"a ""b"", c",3000,"#);
+  }
+
+  #[test]
+  fn format_from_str() {
+    assert_eq!("csv".parse(), Ok(Format::Csv));
+    assert!("xml".parse::<Format>().is_err());
+  }
+}
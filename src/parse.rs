@@ -0,0 +1,151 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// A problem found while parsing puzzle input. Parsers fill in what they
+/// know (message, column); `parse_lines` adds the line, and the runner adds
+/// the file name once it knows where the input came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+  pub file: Option<String>,
+  /// 1-based line number, or 0 when the error is about the input as a whole.
+  pub line: usize,
+  /// 1-based column, in characters.
+  pub column: usize,
+  /// The offending line.
+  pub text: String,
+  pub message: String,
+}
+
+impl ParseError {
+  pub fn new<S: Into<String>>(message: S) -> ParseError {
+    ParseError {
+      file: None,
+      line: 0,
+      column: 1,
+      text: String::new(),
+      message: message.into(),
+    }
+  }
+
+  pub fn at_column(mut self, column: usize) -> ParseError {
+    self.column = column;
+    self
+  }
+
+  pub fn on_line(mut self, line: usize, text: &str) -> ParseError {
+    self.line = line;
+    self.text = text.to_string();
+    self
+  }
+
+  /// Points the error at byte `offset` of `contents`.
+  pub fn at_offset(self, contents: &str, offset: usize) -> ParseError {
+    let line_start = contents[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line = contents[..offset].matches('\n').count() + 1;
+    let column = contents[line_start..offset].chars().count() + 1;
+    self
+      .on_line(line, line_text(contents, line))
+      .at_column(column)
+  }
+
+  pub fn in_file<S: Into<String>>(mut self, file: S) -> ParseError {
+    self.file = Some(file.into());
+    self
+  }
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let file = self.file.as_deref().unwrap_or("<input>");
+    if self.line == 0 {
+      return write!(f, "{}: {}", file, self.message);
+    }
+    writeln!(
+      f,
+      "{}:{}:{}: {}",
+      file, self.line, self.column, self.message
+    )?;
+    writeln!(f, "  {}", self.text)?;
+    write!(f, "  {:>width$}", "^", width = self.column)
+  }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The text of 1-based `line` in `contents`, or "" past the end.
+pub fn line_text(contents: &str, line: usize) -> &str {
+  contents.lines().nth(line.wrapping_sub(1)).unwrap_or("")
+}
+
+/// Parses every line with `f`, tagging any error with the line it came from.
+pub fn parse_lines<T, F>(contents: &str, mut f: F) -> Result<Vec<T>, ParseError>
+where
+  F: FnMut(&str) -> Result<T, ParseError>,
+{
+  contents
+    .lines()
+    .enumerate()
+    .map(|(idx, line)| f(line).map_err(|e| e.on_line(idx + 1, line)))
+    .collect()
+}
+
+/// Reads `path` and parses it with `parse`, or returns `None` if there is no
+/// such file. Parse errors are tagged with the path.
+pub fn load_optional<T, F>(path: &Path, parse: F) -> Result<Option<T>, String>
+where
+  F: FnOnce(&str) -> Result<T, ParseError>,
+{
+  match fs::read_to_string(path) {
+    Ok(contents) => parse(&contents)
+      .map(Some)
+      .map_err(|e| e.in_file(path.display().to_string()).to_string()),
+    Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+    Err(e) => Err(format!("could not read {}: {}", path.display(), e)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn reports_line_and_column() {
+    let err = parse_lines("1\n2\nx3", |l| {
+      l.parse::<u32>()
+        .map_err(|_| ParseError::new("not a number"))
+    })
+    .unwrap_err()
+    .in_file("day1.txt");
+    assert_eq!(err.line, 3);
+    assert_eq!(err.text, "x3");
+    assert_eq!(err.to_string(), "day1.txt:3:1: not a number\n  x3\n  ^");
+  }
+
+  #[test]
+  fn at_offset() {
+    let err = ParseError::new("bad").at_offset("abc\ndeXf", 6);
+    assert_eq!((err.line, err.column), (2, 3));
+    assert_eq!(err.text, "deXf");
+    assert_eq!(err.to_string(), "<input>:2:3: bad\n  deXf\n    ^");
+  }
+
+  #[test]
+  fn load_optional_missing_and_bad_files() {
+    let dir = std::env::temp_dir();
+    let parse = |s: &str| {
+      s.trim()
+        .parse::<u32>()
+        .map_err(|_| ParseError::new("not a number"))
+    };
+    let missing = dir.join(format!("aoc-missing-{}.txt", std::process::id()));
+    assert_eq!(load_optional(&missing, parse), Ok(None));
+
+    let path = dir.join(format!("aoc-load-{}.txt", std::process::id()));
+    fs::write(&path, "x").unwrap();
+    let err = load_optional(&path, parse).unwrap_err();
+    fs::remove_file(&path).unwrap();
+    assert!(err.starts_with(&format!("{}: not a number", path.display())));
+  }
+}
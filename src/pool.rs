@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Number of threads to use when the caller asks for "one per CPU".
+pub fn available() -> usize {
+  thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Runs `work` on every item using up to `threads` worker threads, and hands
+/// each result to `report` on the calling thread in item order, as soon as
+/// it and every earlier result are done.
+pub fn run_ordered<T, R, F, G>(items: &[T], threads: usize, work: F, mut report: G)
+where
+  T: Sync,
+  R: Send,
+  F: Fn(&T) -> R + Sync,
+  G: FnMut(&T, R),
+{
+  let threads = threads.clamp(1, items.len().max(1));
+  let next = AtomicUsize::new(0);
+  let (tx, rx) = mpsc::channel();
+  thread::scope(|scope| {
+    for _ in 0..threads {
+      let tx = tx.clone();
+      let (next, work) = (&next, &work);
+      scope.spawn(move || loop {
+        let idx = next.fetch_add(1, Ordering::SeqCst);
+        if idx >= items.len() || tx.send((idx, work(&items[idx]))).is_err() {
+          break;
+        }
+      });
+    }
+    drop(tx);
+
+    let mut pending = BTreeMap::new();
+    let mut next_report = 0;
+    for (idx, result) in rx {
+      pending.insert(idx, result);
+      while let Some(result) = pending.remove(&next_report) {
+        report(&items[next_report], result);
+        next_report += 1;
+      }
+    }
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Duration;
+
+  #[test]
+  fn reports_in_order() {
+    let items: Vec<u64> = (0..8).collect();
+    let mut seen = vec![];
+    run_ordered(
+      &items,
+      4,
+      |&i| {
+        // finish the early items last
+        thread::sleep(Duration::from_millis(8 - i));
+        i * 10
+      },
+      |&i, r| seen.push((i, r)),
+    );
+    let expected: Vec<(u64, u64)> = items.iter().map(|&i| (i, i * 10)).collect();
+    assert_eq!(seen, expected);
+  }
+
+  #[test]
+  fn no_items() {
+    let items: Vec<u8> = vec![];
+    run_ordered(&items, 3, |_| (), |_, _| panic!("nothing to report"));
+  }
+}
@@ -0,0 +1,216 @@
+use crate::output::json_string;
+use std::cell::RefCell;
+use std::fmt::{self, Write};
+use std::mem;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// One timed section of work and the sections timed inside it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+  pub name: String,
+  /// When the span opened, relative to the first span of the process, so
+  /// spans recorded on different threads line up.
+  pub start: Duration,
+  pub elapsed: Duration,
+  pub children: Vec<Span>,
+}
+
+thread_local! {
+  /// Spans open on this thread, outermost first. Empty when nothing is being
+  /// recorded, which makes `span` a no-op.
+  static OPEN: RefCell<Vec<Span>> = const { RefCell::new(Vec::new()) };
+}
+
+fn since_epoch() -> Duration {
+  static EPOCH: OnceLock<Instant> = OnceLock::new();
+  EPOCH.get_or_init(Instant::now).elapsed()
+}
+
+fn open(name: &str) {
+  let span = Span {
+    name: name.to_string(),
+    start: since_epoch(),
+    elapsed: Duration::default(),
+    children: vec![],
+  };
+  OPEN.with(|open| open.borrow_mut().push(span));
+}
+
+fn close() -> Span {
+  let now = since_epoch();
+  let mut span = OPEN
+    .with(|open| open.borrow_mut().pop())
+    .expect("no open span");
+  span.elapsed = now - span.start;
+  span
+}
+
+/// Runs `f` as the root of a new profile on this thread, returning its result
+/// along with every span opened inside it.
+pub fn record<T, F: FnOnce() -> T>(name: &str, f: F) -> (T, Span) {
+  // set aside anything already being recorded so the new root stands alone
+  let outer = OPEN.with(|open| mem::take(&mut *open.borrow_mut()));
+  open(name);
+  let result = f();
+  let span = close();
+  OPEN.with(|open| *open.borrow_mut() = outer);
+  (result, span)
+}
+
+/// Runs `f`, recording it as a child of the innermost open span. When
+/// nothing is being recorded this just calls `f`, so solutions can mark
+/// their inner sections unconditionally.
+pub fn span<T, F: FnOnce() -> T>(name: &str, f: F) -> T {
+  if OPEN.with(|open| open.borrow().is_empty()) {
+    return f();
+  }
+  open(name);
+  let result = f();
+  let span = close();
+  attach(span);
+  result
+}
+
+/// Adds a span recorded elsewhere, e.g. on another thread, as a child of the
+/// innermost open span. Dropped if nothing is being recorded.
+pub fn attach(span: Span) {
+  OPEN.with(|open| {
+    if let Some(parent) = open.borrow_mut().last_mut() {
+      parent.children.push(span);
+    }
+  });
+}
+
+impl Span {
+  fn walk<'a>(&'a self, depth: usize, f: &mut dyn FnMut(&'a Span, usize)) {
+    f(self, depth);
+    for child in &self.children {
+      child.walk(depth + 1, f);
+    }
+  }
+
+  /// The profile in Chrome's trace event format, for chrome://tracing or
+  /// Perfetto. Each top-level child gets its own track, since they may have
+  /// run on different threads.
+  pub fn to_chrome_trace(&self) -> String {
+    let mut events = vec![];
+    let mut event = |span: &Span, tid: usize| {
+      events.push(format!(
+        r#"{{"name":{},"ph":"X","ts":{:.3},"dur":{:.3},"pid":1,"tid":{}}}"#,
+        json_string(&span.name),
+        span.start.as_nanos() as f64 / 1000.0,
+        span.elapsed.as_nanos() as f64 / 1000.0,
+        tid
+      ))
+    };
+    event(self, 0);
+    for (idx, child) in self.children.iter().enumerate() {
+      child.walk(0, &mut |span, _| event(span, idx + 1));
+    }
+    format!(
+      "{{\"traceEvents\":[\n{}\n],\"displayTimeUnit\":\"ms\"}}\n",
+      events.join(",\n")
+    )
+  }
+}
+
+/// Draws the profile as an indented tree, with each span's share of the
+/// root's time.
+impl fmt::Display for Span {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let mut rows = vec![];
+    self.walk(0, &mut |span, depth| rows.push((depth, span)));
+    let width = rows
+      .iter()
+      .map(|(depth, span)| depth * 2 + span.name.chars().count())
+      .max()
+      .unwrap_or(0);
+    let total = self.elapsed.as_secs_f64();
+    let mut out = String::new();
+    for (depth, span) in rows {
+      let percent = match total {
+        t if t > 0.0 => span.elapsed.as_secs_f64() / t * 100.0,
+        _ => 100.0,
+      };
+      let label = format!("{:indent$}{}", "", span.name, indent = depth * 2);
+      writeln!(
+        out,
+        "{:<width$}  {:>11}  {:>5.1}%",
+        label,
+        format!("{:.2?}", span.elapsed),
+        percent,
+        width = width
+      )?;
+    }
+    f.write_str(out.trim_end())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn names(span: &Span) -> Vec<String> {
+    let mut names = vec![];
+    span.walk(0, &mut |s, depth| {
+      names.push(format!("{}{}", depth, s.name))
+    });
+    names
+  }
+
+  #[test]
+  fn nested_spans() {
+    let (answer, root) = record("day", || {
+      let input = span("parse", || 20);
+      span("part1", || span("inner", || input + 1) * 2)
+    });
+    assert_eq!(answer, 42);
+    assert_eq!(names(&root), vec!["0day", "1parse", "1part1", "2inner"]);
+    let part1 = &root.children[1];
+    assert!(part1.elapsed >= part1.children[0].elapsed);
+    assert!(part1.start >= root.start);
+  }
+
+  #[test]
+  fn span_without_record_is_a_no_op() {
+    assert_eq!(span("alone", || 7), 7);
+    let (_, root) = record("root", || ());
+    assert!(root.children.is_empty());
+  }
+
+  #[test]
+  fn attach_from_another_thread() {
+    let (_, root) = record("all", || {
+      let day = std::thread::spawn(|| record("day1", || span("parse", || ())).1)
+        .join()
+        .unwrap();
+      attach(day);
+    });
+    assert_eq!(names(&root), vec!["0all", "1day1", "2parse"]);
+  }
+
+  #[test]
+  fn tree_and_trace() {
+    let root = Span {
+      name: "all".to_string(),
+      start: Duration::from_micros(10),
+      elapsed: Duration::from_millis(4),
+      children: vec![Span {
+        name: "day\"1".to_string(),
+        start: Duration::from_micros(20),
+        elapsed: Duration::from_millis(1),
+        children: vec![],
+      }],
+    };
+    assert_eq!(
+      root.to_string(),
+      "all           4.00ms  100.0%\n  day\"1       1.00ms   25.0%"
+    );
+    let trace = root.to_chrome_trace();
+    assert!(trace.starts_with("{\"traceEvents\":[\n"));
+    assert!(
+      trace.contains(r#"{"name":"day\"1","ph":"X","ts":20.000,"dur":1000.000,"pid":1,"tid":1}"#)
+    );
+  }
+}
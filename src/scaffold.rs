@@ -0,0 +1,277 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Skeleton for `src/dayN.rs`: a `Solution` over the input's lines with
+/// both parts unanswered, and an example test to fill in.
+pub fn day_module(day: u8) -> String {
+  TEMPLATE.replace("DAY", &day.to_string())
+}
+
+const TEMPLATE: &str = r#"use crate::parse::{parse_lines, ParseError};
+use crate::solution::Solution;
+
+pub struct DayDAY;
+
+impl Solution for DayDAY {
+  type Input = Vec<String>;
+  type Part1 = usize;
+  type Part2 = usize;
+
+  fn parse(&self, contents: &str) -> Result<Vec<String>, ParseError> {
+    parse_lines(contents, |l| Ok(l.to_string()))
+  }
+
+  fn part1(&self, lines: &Vec<String>) -> Option<usize> {
+    part1(lines)
+  }
+
+  fn part2(&self, lines: &Vec<String>) -> Option<usize> {
+    part2(lines)
+  }
+}
+
+fn part1(_lines: &[String]) -> Option<usize> {
+  None
+}
+
+fn part2(_lines: &[String]) -> Option<usize> {
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const EXAMPLE: &str = "";
+
+  #[test]
+  fn example() {
+    let input = DayDAY.parse(EXAMPLE).unwrap();
+    assert_eq!(DayDAY.part1(&input), None);
+    assert_eq!(DayDAY.part2(&input), None);
+  }
+}
+"#;
+
+/// The number in a `dayN` identifier at the start of `s`, if there is one.
+fn day_number(s: &str) -> Option<u8> {
+  let digits: String = s
+    .strip_prefix("day")?
+    .chars()
+    .take_while(char::is_ascii_digit)
+    .collect();
+  digits.parse().ok()
+}
+
+/// Inserts `line` among the lines that `day_of` recognises as belonging to a
+/// day, keeping them in day order. Fails if there are none to go by.
+fn insert_in_day_order<F>(source: &str, line: &str, day: u8, day_of: F) -> Result<String, String>
+where
+  F: Fn(&str) -> Option<u8>,
+{
+  let lines: Vec<&str> = source.lines().collect();
+  let days: Vec<(usize, u8)> = lines
+    .iter()
+    .enumerate()
+    .filter_map(|(idx, l)| day_of(l).map(|d| (idx, d)))
+    .collect();
+  if days.iter().any(|&(_, d)| d == day) {
+    return Err(format!("day {} is already there", day));
+  }
+  let at = match days.iter().find(|&&(_, d)| d > day) {
+    Some(&(idx, _)) => idx,
+    None => days.last().ok_or("found no other days to go by")?.0 + 1,
+  };
+  let mut out: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+  out.insert(at, line.to_string());
+  Ok(out.join("\n") + "\n")
+}
+
+/// Adds `pub mod dayN;` to `src/lib.rs`.
+pub fn add_module(lib_rs: &str, day: u8) -> Result<String, String> {
+  let line = format!("pub mod day{};", day);
+  insert_in_day_order(lib_rs, &line, day, |l| {
+    day_number(l.strip_prefix("pub mod ")?.strip_suffix(';')?)
+  })
+}
+
+/// Adds day `day` to the `use crate::{day1, ...}` line and the list in
+/// `Registry::with_config` in `src/solution.rs`.
+pub fn add_registry_entry(solution_rs: &str, day: u8) -> Result<String, String> {
+  let entry = |l: &str| {
+    let rest = l.trim_start().strip_prefix("Entry::new(")?;
+    rest.split(',').next()?.trim().parse::<u8>().ok()
+  };
+  let indent = solution_rs
+    .lines()
+    .find(|l| entry(l).is_some())
+    .map_or("", |l| &l[..l.len() - l.trim_start().len()]);
+  let line = format!("{}Entry::new({}, day{}::Day{}),", indent, day, day, day);
+  let source = insert_in_day_order(solution_rs, &line, day, entry)?;
+
+  let mut found_use = false;
+  let lines: Vec<String> = source
+    .lines()
+    .map(|l| {
+      let days = match l
+        .strip_prefix("use crate::{")
+        .and_then(|rest| rest.strip_suffix("};"))
+      {
+        Some(days) if days.split(", ").all(|d| day_number(d).is_some()) => days,
+        _ => return l.to_string(),
+      };
+      found_use = true;
+      let mut days: Vec<u8> = days.split(", ").filter_map(day_number).collect();
+      days.push(day);
+      days.sort_unstable();
+      let days: Vec<String> = days.iter().map(|d| format!("day{}", d)).collect();
+      format!("use crate::{{{}}};", days.join(", "))
+    })
+    .collect();
+  if !found_use {
+    return Err("found no `use crate::{day1, ...};` line".to_string());
+  }
+  Ok(lines.join("\n") + "\n")
+}
+
+/// The checkout to generate into: the current directory when it is one,
+/// otherwise the one this binary was built from.
+pub fn default_root() -> PathBuf {
+  if Path::new("src/solution.rs").is_file() {
+    PathBuf::from(".")
+  } else {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+  }
+}
+
+/// Creates `src/dayN.rs` and an empty `data/dayN.txt` under `root` and
+/// registers the day, returning the files it wrote. Nothing is written if
+/// the day already exists or the sources can't be updated, and if a write
+/// fails partway the ones before it are undone.
+pub fn new_day(root: &Path, day: u8) -> Result<Vec<PathBuf>, String> {
+  let module = root.join(format!("src/day{}.rs", day));
+  let data_dir = root.join("data");
+  let data = data_dir.join(format!("day{}.txt", day));
+  let lib = root.join("src/lib.rs");
+  let solution = root.join("src/solution.rs");
+  if module.exists() {
+    return Err(format!("{} already exists", module.display()));
+  }
+  let read = |path: &Path| {
+    fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path.display(), e))
+  };
+  let lib_before = read(&lib)?;
+  let solution_before = read(&solution)?;
+  let lib_rs = add_module(&lib_before, day).map_err(|e| format!("{}: {}", lib.display(), e))?;
+  let solution_rs = add_registry_entry(&solution_before, day)
+    .map_err(|e| format!("{}: {}", solution.display(), e))?;
+  fs::create_dir_all(&data_dir)
+    .map_err(|e| format!("could not create {}: {}", data_dir.display(), e))?;
+
+  // each file with what to put back if a later write fails: `None` to
+  // remove it. The data file goes first so the sources are touched last.
+  let mut writes = vec![];
+  // keep an input that's already been downloaded
+  if !data.exists() {
+    writes.push((data, String::new(), None));
+  }
+  writes.push((module, day_module(day), None));
+  writes.push((lib, lib_rs, Some(lib_before)));
+  writes.push((solution, solution_rs, Some(solution_before)));
+  for (idx, (path, contents, _)) in writes.iter().enumerate() {
+    if let Err(e) = fs::write(path, contents) {
+      for (path, _, before) in &writes[..idx] {
+        // best effort: the write error is the one to report
+        let _ = match before {
+          Some(before) => fs::write(path, before),
+          None => fs::remove_file(path),
+        };
+      }
+      return Err(format!("could not write {}: {}", path.display(), e));
+    }
+  }
+  Ok(writes.into_iter().map(|(path, _, _)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn module_skeleton() {
+    let module = day_module(12);
+    assert!(module.contains("pub struct Day12;\n"));
+    assert!(module.contains("impl Solution for Day12 {"));
+    assert!(module.contains("let input = Day12.parse(EXAMPLE).unwrap();"));
+  }
+
+  #[test]
+  fn adds_module_in_order() {
+    let lib =
+      "//! docs\n\npub mod cli;\npub mod day1;\npub mod day2;\npub mod day10;\npub mod input;\n";
+    assert_eq!(
+      add_module(lib, 3).unwrap(),
+      "//! docs\n\npub mod cli;\npub mod day1;\npub mod day2;\npub mod day3;\npub mod day10;\npub mod input;\n"
+    );
+    assert_eq!(
+      add_module(lib, 11).unwrap(),
+      "//! docs\n\npub mod cli;\npub mod day1;\npub mod day2;\npub mod day10;\npub mod day11;\npub mod input;\n"
+    );
+    assert!(add_module(lib, 2).is_err());
+    assert!(add_module("pub mod cli;\n", 2).is_err());
+  }
+
+  #[test]
+  fn registers_day() {
+    let solution = "\
+use crate::{day1, day2};
+
+      entries: vec![
+        Entry::new(1, day1::Day1),
+        Entry::new(2, day2::Day2),
+      ],
+";
+    assert_eq!(
+      add_registry_entry(solution, 8).unwrap(),
+      "\
+use crate::{day1, day2, day8};
+
+      entries: vec![
+        Entry::new(1, day1::Day1),
+        Entry::new(2, day2::Day2),
+        Entry::new(8, day8::Day8),
+      ],
+"
+    );
+    assert!(add_registry_entry(solution, 2).is_err());
+  }
+
+  #[test]
+  fn creates_missing_data_dir() {
+    let root = std::env::temp_dir().join(format!("aoc-new-day-{}", std::process::id()));
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/lib.rs"), "pub mod day1;\n").unwrap();
+    fs::write(
+      root.join("src/solution.rs"),
+      "use crate::{day1};\n        Entry::new(1, day1::Day1),\n",
+    )
+    .unwrap();
+    let written = new_day(&root, 9);
+    let data = root.join("data/day9.txt").is_file();
+    let module = root.join("src/day9.rs").is_file();
+    fs::remove_dir_all(&root).unwrap();
+    assert_eq!(written.map(|paths| paths.len()), Ok(4));
+    assert!(data && module);
+  }
+
+  #[test]
+  fn registers_against_this_crate() {
+    let solution = include_str!("solution.rs");
+    let updated = add_registry_entry(solution, 25).unwrap();
+    assert!(updated.contains(", day25};"));
+    assert!(updated.contains("        Entry::new(25, day25::Day25),\n      ],"));
+    assert!(add_module(include_str!("lib.rs"), 25)
+      .unwrap()
+      .contains("pub mod day25;\npub mod input;"));
+  }
+}
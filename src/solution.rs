@@ -0,0 +1,163 @@
+use crate::config::Config;
+use crate::parse::ParseError;
+use crate::{day1, day2, day3, day4, day5, day6, day7};
+use std::any::Any;
+use std::fmt::Display;
+use std::sync::Arc;
+
+/// A single day's puzzle: parse the input once, then answer both parts from it.
+/// A part returns `None` when the input has no answer.
+pub trait Solution {
+  type Input: 'static;
+  type Part1: Display;
+  type Part2: Display;
+
+  fn parse(&self, contents: &str) -> Result<Self::Input, ParseError>;
+  fn part1(&self, input: &Self::Input) -> Option<Self::Part1>;
+  fn part2(&self, input: &Self::Input) -> Option<Self::Part2>;
+}
+
+/// Object-safe view of a `Solution`, so days with different input and answer
+/// types can share one registry. Answers are rendered with `Display`. Days
+/// are `Sync` so independent days can be run on separate threads.
+pub trait Day: Sync {
+  fn parse(&self, contents: &str) -> Result<Box<dyn Any>, ParseError>;
+  fn part1(&self, input: &dyn Any) -> Option<String>;
+  fn part2(&self, input: &dyn Any) -> Option<String>;
+}
+
+impl<S: Solution + Sync> Day for S {
+  fn parse(&self, contents: &str) -> Result<Box<dyn Any>, ParseError> {
+    Ok(Box::new(Solution::parse(self, contents)?))
+  }
+  fn part1(&self, input: &dyn Any) -> Option<String> {
+    Solution::part1(self, downcast::<S>(input)).map(|a| a.to_string())
+  }
+  fn part2(&self, input: &dyn Any) -> Option<String> {
+    Solution::part2(self, downcast::<S>(input)).map(|a| a.to_string())
+  }
+}
+
+fn downcast<S: Solution>(input: &dyn Any) -> &S::Input {
+  input
+    .downcast_ref::<S::Input>()
+    .expect("input was parsed by a different solution")
+}
+
+/// A registered day. Cloning is cheap, so a day can be handed to a thread
+/// that may outlive the registry's borrow.
+#[derive(Clone)]
+pub struct Entry {
+  pub day: u8,
+  pub solution: Arc<dyn Day + Send>,
+}
+
+impl Entry {
+  /// Runs part 1 or part 2 against an input parsed by this entry.
+  pub fn solve(&self, part: u8, input: &dyn Any) -> Option<String> {
+    match part {
+      1 => self.solution.part1(input),
+      2 => self.solution.part2(input),
+      _ => panic!("there is no part {}", part),
+    }
+  }
+
+  fn new<S: Solution + Send + Sync + 'static>(day: u8, solution: S) -> Entry {
+    Entry {
+      day,
+      solution: Arc::new(solution),
+    }
+  }
+}
+
+/// Every implemented day, in day order.
+pub struct Registry {
+  entries: Vec<Entry>,
+}
+
+impl Registry {
+  /// Every day with its default parameters, as the real puzzle inputs need.
+  pub fn new() -> Registry {
+    Registry::with_config(&Config::default()).expect("defaults are valid")
+  }
+
+  /// Every day, with parameters from `config` where it sets them.
+  pub fn with_config(config: &Config) -> Result<Registry, String> {
+    let mut config = config.clone();
+    let registry = Registry {
+      entries: vec![
+        Entry::new(1, day1::Day1::<i64>::default()),
+        Entry::new(2, day2::Day2),
+        Entry::new(3, day3::Day3),
+        Entry::new(4, day4::Day4),
+        Entry::new(5, day5::Day5),
+        Entry::new(6, day6::Day6::from_params(config.take(6))?),
+        Entry::new(7, day7::Day7::from_params(config.take(7))?),
+      ],
+    };
+    config.finish()?;
+    Ok(registry)
+  }
+
+  pub fn get(&self, day: u8) -> Option<&Entry> {
+    self.entries.iter().find(|e| e.day == day)
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+    self.entries.iter()
+  }
+}
+
+impl Default for Registry {
+  fn default() -> Registry {
+    Registry::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn registry_is_in_day_order() {
+    let days: Vec<u8> = Registry::new().iter().map(|e| e.day).collect();
+    assert_eq!(days[..7], [1, 2, 3, 4, 5, 6, 7]);
+    // `aoc new-day` can add any day, so later ones need only stay in order
+    assert!(days.windows(2).all(|w| w[0] < w[1]));
+  }
+
+  #[test]
+  fn dyn_day_answers() {
+    let registry = Registry::new();
+    let entry = registry.get(5).unwrap();
+    let input = entry.solution.parse("dabAcCaCBAcCcaDA").unwrap();
+    assert_eq!(entry.solution.part1(&*input), Some("10".to_string()));
+    assert_eq!(entry.solution.part2(&*input), Some("4".to_string()));
+  }
+
+  #[test]
+  fn configured_days() {
+    let mut config = Config::parse("[day7]\nworkers = 2\nbase_time = 0\n").unwrap();
+    let registry = Registry::with_config(&config).unwrap();
+    let entry = registry.get(7).unwrap();
+    let input = entry
+      .solution
+      .parse("Step A must be finished before step B can begin.\n")
+      .unwrap();
+    assert_eq!(entry.solve(2, &*input), Some("3".to_string()));
+    config.set(3, "claims", 1);
+    assert!(Registry::with_config(&config).is_err());
+  }
+
+  #[test]
+  fn dyn_day_parse_error() {
+    let registry = Registry::new();
+    let err = registry
+      .get(3)
+      .unwrap()
+      .solution
+      .parse("#1 @ 1,3: 4x4\n#2 @ 3,1\n")
+      .unwrap_err();
+    assert_eq!(err.line, 2);
+  }
+}
@@ -0,0 +1,101 @@
+use std::cell::RefCell;
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+thread_local! {
+  /// Set on threads started by `with_limit`, and raised once the caller has
+  /// stopped waiting.
+  static CANCEL: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// Whether the work running on this thread has run out of time. Solutions
+/// with loops that might not end can poll this and give up early; nothing
+/// else is needed for the run to carry on without them.
+pub fn cancelled() -> bool {
+  CANCEL.with(|cancel| {
+    cancel
+      .borrow()
+      .as_ref()
+      .is_some_and(|c| c.load(Ordering::Relaxed))
+  })
+}
+
+/// Runs `f` on its own thread and waits up to `limit` for it, returning
+/// `None` if it takes longer. A late `f` is asked to stop through
+/// `cancelled`, but is otherwise left running in the background so the
+/// caller can move on; it dies with the process if it never finishes. If
+/// `f` panics, the panic carries on in the caller, as if `f` had been
+/// called directly.
+pub fn with_limit<T, F>(limit: Duration, f: F) -> Option<T>
+where
+  T: Send + 'static,
+  F: FnOnce() -> T + Send + 'static,
+{
+  let cancel = Arc::new(AtomicBool::new(false));
+  let (tx, rx) = mpsc::channel();
+  let flag = Arc::clone(&cancel);
+  let handle = thread::spawn(move || {
+    CANCEL.with(|cancel| *cancel.borrow_mut() = Some(flag));
+    // the receiver is gone if we were too slow
+    let _ = tx.send(f());
+  });
+  match rx.recv_timeout(limit) {
+    Ok(result) => Some(result),
+    Err(RecvTimeoutError::Timeout) => {
+      cancel.store(true, Ordering::Relaxed);
+      None
+    }
+    // `f` panicked before it could send anything
+    Err(RecvTimeoutError::Disconnected) => match handle.join() {
+      Err(payload) => panic::resume_unwind(payload),
+      Ok(()) => unreachable!("the worker finished without sending a result"),
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Instant;
+
+  #[test]
+  fn finishes_in_time() {
+    assert_eq!(with_limit(Duration::from_secs(10), || 6 * 7), Some(42));
+  }
+
+  #[test]
+  fn gives_up_on_runaway_work() {
+    let (tx, rx) = mpsc::channel();
+    let start = Instant::now();
+    let result = with_limit(Duration::from_millis(20), move || {
+      while !cancelled() {
+        thread::yield_now();
+      }
+      tx.send("stopped").unwrap();
+    });
+    assert_eq!(result, None);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    // the abandoned work sees the cancellation and stops
+    assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok("stopped"));
+  }
+
+  #[test]
+  fn panics_are_not_timeouts() {
+    let start = Instant::now();
+    let result = panic::catch_unwind(|| {
+      with_limit(Duration::from_secs(10), || -> u32 { panic!("bad input") })
+    });
+    let payload = result.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"bad input"));
+    assert!(start.elapsed() < Duration::from_secs(5));
+  }
+
+  #[test]
+  fn not_cancelled_outside_with_limit() {
+    assert!(!cancelled());
+  }
+}
@@ -1,41 +1,16 @@
-#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
-pub struct Point2 {
-  pub x: u32,
-  pub y: u32,
-}
-impl Point2 {
-  pub fn new(x: u32, y: u32) -> Point2 {
-    Point2 { x, y }
-  }
-  pub fn manhattan_dist(&self, o: &Self) -> u32 {
-    ((self.x as i32 - o.x as i32).abs() + (self.y as i32 - o.y as i32).abs()) as u32
-  }
-}
+use crate::parse::ParseError;
 
-pub fn scan_ints(s: &str) -> Vec<i32> {
-  let mut result = vec![];
-  let mut digit_chars = String::new();
-  for c in s.chars() {
-    match (c.is_numeric(), digit_chars.len()) {
-      (true, _) => digit_chars.push(c),
-      (false, len) if len > 0 => {
-        result.push(digit_chars.parse().unwrap());
-        digit_chars.clear();
-      }
-      _ => continue,
-    }
-  }
-  if !digit_chars.is_empty() {
-    result.push(digit_chars.parse().unwrap());
-  }
-  result
-}
+mod grid;
+mod point;
+mod scan;
+
+pub use grid::Grid;
+pub use point::{Coord, Dir4, Dir8, Point2, Point3, Point4, Signed};
+pub use scan::{ints, Ints, ScanInt};
 
-pub fn time<F: FnOnce() -> ()>(desc: &str, closure: F) {
-  use std::time::Instant;
-  let now = Instant::now();
-  closure();
-  println!("Ran {} in {:?}", desc, Instant::now().duration_since(now));
+/// Every integer in `s`, collected. See `ints` for the rules.
+pub fn scan_ints<T: ScanInt>(s: &str) -> Result<Vec<T>, ParseError> {
+  ints(s).collect()
 }
 
 #[cfg(test)]
@@ -44,6 +19,6 @@ mod tests {
 
   #[test]
   fn scan() {
-    assert_eq!(vec![1, 2, 3], scan_ints("adsf1,2    @3"))
+    assert_eq!(Ok(vec![1, 2, 3]), scan_ints::<i32>("adsf1,2    @3"))
   }
 }
@@ -0,0 +1,352 @@
+use super::Point2;
+use crate::parse::ParseError;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A dense, rectangular 2D grid covering every point from `min` to `max`
+/// inclusive, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+  min: Point2,
+  width: u32,
+  height: u32,
+  cells: Vec<T>,
+}
+
+/// The number of cells in a `width` x `height` grid, or an error if there
+/// are too many to index.
+fn cell_count(width: u32, height: u32) -> Result<usize, String> {
+  (width as usize)
+    .checked_mul(height as usize)
+    .ok_or_else(|| format!("a {}x{} grid is too large", width, height))
+}
+
+impl<T: Clone> Grid<T> {
+  /// A `width` x `height` grid with its top-left corner at (0, 0).
+  pub fn new(width: u32, height: u32, fill: T) -> Result<Grid<T>, String> {
+    Ok(Grid {
+      min: Point2::new(0, 0),
+      width,
+      height,
+      cells: vec![fill; cell_count(width, height)?],
+    })
+  }
+}
+
+impl<T> Grid<T> {
+  /// A grid covering `min..=max`, with each cell set to `f(point)`.
+  pub fn from_fn<F>(min: Point2, max: Point2, f: F) -> Result<Grid<T>, String>
+  where
+    F: FnMut(Point2) -> T,
+  {
+    if min.x > max.x || min.y > max.y {
+      return Err(format!("grid max {:?} is before min {:?}", max, min));
+    }
+    let too_large = || format!("a grid from {:?} to {:?} is too large", min, max);
+    let width = (max.x - min.x).checked_add(1).ok_or_else(too_large)?;
+    let height = (max.y - min.y).checked_add(1).ok_or_else(too_large)?;
+    cell_count(width, height)?;
+    let mut grid = Grid {
+      min,
+      width,
+      height,
+      cells: vec![],
+    };
+    grid.cells = grid.points().map(f).collect();
+    Ok(grid)
+  }
+
+  /// Parses a character map such as a puzzle's `#.#` drawing, one row per
+  /// line, converting each character with `f`. Rows must all be the same
+  /// length.
+  pub fn parse_with<F>(s: &str, mut f: F) -> Result<Grid<T>, ParseError>
+  where
+    F: FnMut(char) -> Result<T, ParseError>,
+  {
+    let mut width = None;
+    let mut cells = vec![];
+    for (idx, line) in s.lines().enumerate() {
+      let row_start = cells.len();
+      for (col, c) in line.chars().enumerate() {
+        let cell = f(c).map_err(|e| e.on_line(idx + 1, line).at_column(col + 1))?;
+        cells.push(cell);
+      }
+      let row_width = cells.len() - row_start;
+      match width {
+        None => width = Some(row_width),
+        Some(w) if w != row_width => {
+          return Err(
+            ParseError::new(format!(
+              "expected a row of {} cells, found {}",
+              w, row_width
+            ))
+            .on_line(idx + 1, line),
+          )
+        }
+        _ => {}
+      }
+    }
+    let width = width.unwrap_or(0) as u32;
+    let height = (cells.len() as u32).checked_div(width).unwrap_or(0);
+    Ok(Grid {
+      min: Point2::new(0, 0),
+      width,
+      height,
+      cells,
+    })
+  }
+
+  pub fn width(&self) -> u32 {
+    self.width
+  }
+
+  pub fn height(&self) -> u32 {
+    self.height
+  }
+
+  pub fn min(&self) -> Point2 {
+    self.min
+  }
+
+  /// The bottom-right corner, inclusive, or `None` if the grid is empty.
+  pub fn max(&self) -> Option<Point2> {
+    if self.width == 0 || self.height == 0 {
+      return None;
+    }
+    Some(Point2::new(
+      self.min.x + self.width - 1,
+      self.min.y + self.height - 1,
+    ))
+  }
+
+  pub fn contains(&self, p: Point2) -> bool {
+    p.x >= self.min.x
+      && p.y >= self.min.y
+      && p.x - self.min.x < self.width
+      && p.y - self.min.y < self.height
+  }
+
+  /// Whether `p` is on the outermost ring of the grid.
+  pub fn is_edge(&self, p: Point2) -> bool {
+    match self.max() {
+      Some(max) if self.contains(p) => {
+        p.x == self.min.x || p.y == self.min.y || p.x == max.x || p.y == max.y
+      }
+      _ => false,
+    }
+  }
+
+  fn offset(&self, p: Point2) -> Option<usize> {
+    if self.contains(p) {
+      let (x, y) = (p.x - self.min.x, p.y - self.min.y);
+      // can't overflow: the cells all fit in memory
+      Some(y as usize * self.width as usize + x as usize)
+    } else {
+      None
+    }
+  }
+
+  pub fn get(&self, p: Point2) -> Option<&T> {
+    self.offset(p).map(|i| &self.cells[i])
+  }
+
+  pub fn get_mut(&mut self, p: Point2) -> Option<&mut T> {
+    self.offset(p).map(move |i| &mut self.cells[i])
+  }
+
+  /// Every point in the grid, row by row.
+  pub fn points(&self) -> impl Iterator<Item = Point2> {
+    let (min, width, height) = (self.min, self.width, self.height);
+    (0..height).flat_map(move |y| (0..width).map(move |x| Point2::new(min.x + x, min.y + y)))
+  }
+
+  /// Every point along with its cell, row by row.
+  pub fn iter(&self) -> impl Iterator<Item = (Point2, &T)> {
+    self.points().zip(self.cells.iter())
+  }
+
+  pub fn values(&self) -> impl Iterator<Item = &T> {
+    self.cells.iter()
+  }
+
+  pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+    self.cells.iter_mut()
+  }
+
+  /// Rows from top to bottom.
+  pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+    self.cells.chunks(self.width.max(1) as usize)
+  }
+
+  /// The cells of column `x` (in grid coordinates) from top to bottom.
+  pub fn column(&self, x: u32) -> impl Iterator<Item = &T> {
+    let width = self.width as usize;
+    let start = x.wrapping_sub(self.min.x) as usize;
+    let cells: &[T] = if start < width {
+      &self.cells[start..]
+    } else {
+      &[]
+    };
+    cells.iter().step_by(width.max(1))
+  }
+
+  /// The in-bounds points above, below, left and right of `p`.
+  pub fn neighbors4(&self, p: Point2) -> impl Iterator<Item = Point2> + '_ {
+    self.neighbors(p, &[(0, -1), (-1, 0), (1, 0), (0, 1)])
+  }
+
+  /// The in-bounds points surrounding `p`, diagonals included.
+  pub fn neighbors8(&self, p: Point2) -> impl Iterator<Item = Point2> + '_ {
+    self.neighbors(
+      p,
+      &[
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (-1, 0),
+        (1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+      ],
+    )
+  }
+
+  fn neighbors(
+    &self,
+    p: Point2,
+    deltas: &'static [(i64, i64)],
+  ) -> impl Iterator<Item = Point2> + '_ {
+    deltas.iter().filter_map(move |&(dx, dy)| {
+      let x = i64::from(p.x) + dx;
+      let y = i64::from(p.y) + dy;
+      if x < 0 || y < 0 || x > i64::from(u32::MAX) || y > i64::from(u32::MAX) {
+        return None;
+      }
+      let n = Point2::new(x as u32, y as u32);
+      if self.contains(n) {
+        Some(n)
+      } else {
+        None
+      }
+    })
+  }
+}
+
+impl Grid<char> {
+  pub fn parse(s: &str) -> Result<Grid<char>, ParseError> {
+    Grid::parse_with(s, Ok)
+  }
+}
+
+impl<T> Index<Point2> for Grid<T> {
+  type Output = T;
+  fn index(&self, p: Point2) -> &T {
+    match self.offset(p) {
+      Some(i) => &self.cells[i],
+      None => panic!("{:?} is outside the grid", p),
+    }
+  }
+}
+
+impl<T> IndexMut<Point2> for Grid<T> {
+  fn index_mut(&mut self, p: Point2) -> &mut T {
+    match self.offset(p) {
+      Some(i) => &mut self.cells[i],
+      None => panic!("{:?} is outside the grid", p),
+    }
+  }
+}
+
+/// Draws the grid one row per line, with each cell's `Display` output side
+/// by side.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (idx, row) in self.rows().enumerate() {
+      if idx > 0 {
+        writeln!(f)?;
+      }
+      for cell in row {
+        write!(f, "{}", cell)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const MAP: &str = "\
+#.#
+..#
+##.";
+
+  #[test]
+  fn parse_and_display() {
+    let grid = Grid::parse(MAP).unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 3));
+    assert_eq!(grid[Point2::new(2, 1)], '#');
+    assert_eq!(grid.get(Point2::new(3, 0)), None);
+    assert_eq!(grid.to_string(), MAP);
+  }
+
+  #[test]
+  fn ragged_rows() {
+    let err = Grid::parse("##\n#\n").unwrap_err();
+    assert_eq!(err.line, 2);
+  }
+
+  #[test]
+  fn rows_and_columns() {
+    let grid = Grid::parse(MAP).unwrap();
+    let column: String = grid.column(1).collect();
+    assert_eq!(column, "..#");
+    let rows: Vec<String> = grid.rows().map(|r| r.iter().collect()).collect();
+    assert_eq!(rows, vec!["#.#", "..#", "##."]);
+  }
+
+  #[test]
+  fn offset_bounds() {
+    let mut grid = Grid::from_fn(Point2::new(10, 20), Point2::new(12, 21), |p| p.x + p.y).unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid[Point2::new(12, 21)], 33);
+    grid[Point2::new(10, 20)] = 0;
+    assert_eq!(grid.values().filter(|&&v| v == 0).count(), 1);
+    assert!(grid.is_edge(Point2::new(11, 20)));
+    assert!(!grid.contains(Point2::new(9, 20)));
+    let column: Vec<u32> = grid.column(11).cloned().collect();
+    assert_eq!(column, vec![31, 32]);
+  }
+
+  #[test]
+  fn far_from_the_origin() {
+    let min = Point2::new(70_000, 100_000);
+    let mut grid = Grid::from_fn(min, Point2::new(70_001, 100_001), |_| 0).unwrap();
+    grid[Point2::new(70_001, 100_000)] = 1;
+    grid[Point2::new(70_000, 100_001)] = 2;
+    assert_eq!(grid.values().cloned().collect::<Vec<_>>(), vec![0, 1, 2, 0]);
+    assert!(Grid::from_fn(Point2::new(0, 0), Point2::new(u32::MAX, 0), |_| 0).is_err());
+  }
+
+  #[test]
+  fn empty_grids() {
+    assert_eq!(Grid::parse("").unwrap().max(), None);
+    let grid = Grid::new(0, 0, 0).unwrap();
+    assert_eq!(grid.max(), None);
+    assert!(!grid.is_edge(Point2::new(0, 0)));
+    assert_eq!(Grid::new(2, 3, 0).unwrap().max(), Some(Point2::new(1, 2)));
+    assert!(Grid::from_fn(Point2::new(1, 0), Point2::new(0, 0), |_| 0).is_err());
+  }
+
+  #[test]
+  fn neighbors() {
+    let grid = Grid::new(3, 3, 0).unwrap();
+    assert_eq!(grid.neighbors4(Point2::new(1, 1)).count(), 4);
+    assert_eq!(grid.neighbors8(Point2::new(1, 1)).count(), 8);
+    let corner: Vec<Point2> = grid.neighbors4(Point2::new(0, 0)).collect();
+    assert_eq!(corner, vec![Point2::new(1, 0), Point2::new(0, 1)]);
+    assert_eq!(grid.neighbors8(Point2::new(2, 2)).count(), 3);
+  }
+}
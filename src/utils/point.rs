@@ -0,0 +1,357 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// An integer type usable as a point coordinate.
+pub trait Coord:
+  Copy + Ord + Default + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+  /// Unsigned type that holds the distance between any two coordinates, so
+  /// e.g. `i32::MIN` to `i32::MAX` doesn't overflow.
+  type Dist: Copy + Ord + Default + Add<Output = Self::Dist>;
+  const ONE: Self;
+  fn dist(self, o: Self) -> Self::Dist;
+  fn checked_add_dist(a: Self::Dist, b: Self::Dist) -> Option<Self::Dist>;
+  fn to_f64(self) -> f64;
+}
+
+/// A coordinate type that can point backwards, needed for directions and
+/// rotation.
+pub trait Signed: Coord + Neg<Output = Self> {}
+
+macro_rules! coord {
+  ($($t:ty => $d:ty),*) => {$(
+    impl Coord for $t {
+      type Dist = $d;
+      const ONE: $t = 1;
+      fn dist(self, o: $t) -> $d {
+        self.abs_diff(o)
+      }
+      fn checked_add_dist(a: $d, b: $d) -> Option<$d> {
+        a.checked_add(b)
+      }
+      fn to_f64(self) -> f64 {
+        self as f64
+      }
+    }
+  )*};
+}
+
+coord!(
+  u8 => u8, u16 => u16, u32 => u32, u64 => u64, u128 => u128, usize => usize,
+  i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize
+);
+
+impl Signed for i8 {}
+impl Signed for i16 {}
+impl Signed for i32 {}
+impl Signed for i64 {}
+impl Signed for i128 {}
+impl Signed for isize {}
+
+macro_rules! point {
+  ($(#[$doc:meta])* $name:ident { $($f:ident),+ }) => {
+    $(#[$doc])*
+    #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+    pub struct $name<T = u32> {
+      $(pub $f: T),+
+    }
+
+    impl<T: Coord> $name<T> {
+      pub fn new($($f: T),+) -> $name<T> {
+        $name { $($f),+ }
+      }
+
+      pub fn origin() -> $name<T> {
+        $name::default()
+      }
+
+      /// Sum of the distances along each axis.
+      ///
+      /// Panics if the sum doesn't fit in `T::Dist`, which takes points more
+      /// than half the coordinate range apart on two or more axes; use
+      /// `checked_manhattan_dist` when that can happen.
+      pub fn manhattan_dist(&self, o: &Self) -> T::Dist {
+        self
+          .checked_manhattan_dist(o)
+          .expect("manhattan distance overflows")
+      }
+
+      /// Sum of the distances along each axis, or `None` if it doesn't fit
+      /// in `T::Dist`.
+      pub fn checked_manhattan_dist(&self, o: &Self) -> Option<T::Dist> {
+        let dist = T::Dist::default();
+        $(let dist = T::checked_add_dist(dist, self.$f.dist(o.$f))?;)+
+        Some(dist)
+      }
+
+      /// Largest distance along any one axis (king moves on a chessboard).
+      pub fn chebyshev_dist(&self, o: &Self) -> T::Dist {
+        let mut max = T::Dist::default();
+        $(max = max.max(self.$f.dist(o.$f));)+
+        max
+      }
+
+      /// Straight-line distance.
+      pub fn euclidean_dist(&self, o: &Self) -> f64 {
+        (0.0 $(+ (self.$f.to_f64() - o.$f.to_f64()).powi(2))+).sqrt()
+      }
+    }
+
+    impl<T: Coord> Add for $name<T> {
+      type Output = $name<T>;
+      fn add(self, o: $name<T>) -> $name<T> {
+        $name { $($f: self.$f + o.$f),+ }
+      }
+    }
+
+    impl<T: Coord> Sub for $name<T> {
+      type Output = $name<T>;
+      fn sub(self, o: $name<T>) -> $name<T> {
+        $name { $($f: self.$f - o.$f),+ }
+      }
+    }
+
+    impl<T: Coord> AddAssign for $name<T> {
+      fn add_assign(&mut self, o: $name<T>) {
+        *self = *self + o;
+      }
+    }
+
+    impl<T: Coord> SubAssign for $name<T> {
+      fn sub_assign(&mut self, o: $name<T>) {
+        *self = *self - o;
+      }
+    }
+
+    /// Scales every coordinate.
+    impl<T: Coord> Mul<T> for $name<T> {
+      type Output = $name<T>;
+      fn mul(self, k: T) -> $name<T> {
+        $name { $($f: self.$f * k),+ }
+      }
+    }
+
+    impl<T: Signed> Neg for $name<T> {
+      type Output = $name<T>;
+      fn neg(self) -> $name<T> {
+        $name { $($f: -self.$f),+ }
+      }
+    }
+  };
+}
+
+point!(
+  /// A point on a plane. Puzzle maps put y = 0 at the top, so "up" is -y.
+  Point2 { x, y }
+);
+point!(
+  /// A point in space.
+  Point3 { x, y, z }
+);
+point!(
+  /// A point in four dimensions, e.g. space plus time.
+  Point4 { x, y, z, w }
+);
+
+impl<T: Signed> Point2<T> {
+  /// Rotates 90° clockwise about the origin, as drawn on a puzzle map.
+  pub fn rotate_right(self) -> Point2<T> {
+    Point2::new(-self.y, self.x)
+  }
+
+  /// Rotates 90° counter-clockwise about the origin, as drawn on a puzzle map.
+  pub fn rotate_left(self) -> Point2<T> {
+    Point2::new(self.y, -self.x)
+  }
+}
+
+/// The four orthogonal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dir4 {
+  Up,
+  Right,
+  Down,
+  Left,
+}
+
+impl Dir4 {
+  /// Clockwise from up.
+  pub const ALL: [Dir4; 4] = [Dir4::Up, Dir4::Right, Dir4::Down, Dir4::Left];
+
+  fn index(self) -> usize {
+    self as usize
+  }
+
+  pub fn turn_right(self) -> Dir4 {
+    Dir4::ALL[(self.index() + 1) % 4]
+  }
+
+  pub fn turn_left(self) -> Dir4 {
+    Dir4::ALL[(self.index() + 3) % 4]
+  }
+
+  pub fn reverse(self) -> Dir4 {
+    Dir4::ALL[(self.index() + 2) % 4]
+  }
+
+  /// One step in this direction.
+  pub fn delta<T: Signed>(self) -> Point2<T> {
+    let zero = T::default();
+    match self {
+      Dir4::Up => Point2::new(zero, -T::ONE),
+      Dir4::Right => Point2::new(T::ONE, zero),
+      Dir4::Down => Point2::new(zero, T::ONE),
+      Dir4::Left => Point2::new(-T::ONE, zero),
+    }
+  }
+}
+
+/// The eight orthogonal and diagonal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dir8 {
+  Up,
+  UpRight,
+  Right,
+  DownRight,
+  Down,
+  DownLeft,
+  Left,
+  UpLeft,
+}
+
+impl Dir8 {
+  /// Clockwise from up.
+  pub const ALL: [Dir8; 8] = [
+    Dir8::Up,
+    Dir8::UpRight,
+    Dir8::Right,
+    Dir8::DownRight,
+    Dir8::Down,
+    Dir8::DownLeft,
+    Dir8::Left,
+    Dir8::UpLeft,
+  ];
+
+  fn index(self) -> usize {
+    self as usize
+  }
+
+  /// Turns 45° clockwise.
+  pub fn turn_right(self) -> Dir8 {
+    Dir8::ALL[(self.index() + 1) % 8]
+  }
+
+  /// Turns 45° counter-clockwise.
+  pub fn turn_left(self) -> Dir8 {
+    Dir8::ALL[(self.index() + 7) % 8]
+  }
+
+  pub fn reverse(self) -> Dir8 {
+    Dir8::ALL[(self.index() + 4) % 8]
+  }
+
+  /// One step in this direction.
+  pub fn delta<T: Signed>(self) -> Point2<T> {
+    let (zero, one) = (T::default(), T::ONE);
+    let (x, y) = match self {
+      Dir8::Up => (zero, -one),
+      Dir8::UpRight => (one, -one),
+      Dir8::Right => (one, zero),
+      Dir8::DownRight => (one, one),
+      Dir8::Down => (zero, one),
+      Dir8::DownLeft => (-one, one),
+      Dir8::Left => (-one, zero),
+      Dir8::UpLeft => (-one, -one),
+    };
+    Point2::new(x, y)
+  }
+}
+
+impl From<Dir4> for Dir8 {
+  fn from(d: Dir4) -> Dir8 {
+    Dir8::ALL[d.index() * 2]
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn arithmetic() {
+    let p = Point2::new(3i32, -4);
+    assert_eq!(p + Point2::new(1, 1), Point2::new(4, -3));
+    assert_eq!(p - Point2::new(1, 1), Point2::new(2, -5));
+    assert_eq!(p * 2, Point2::new(6, -8));
+    assert_eq!(-p, Point2::new(-3, 4));
+    let mut q = Point3::new(1i64, 2, 3);
+    q += Point3::new(1, 1, 1);
+    assert_eq!(q, Point3::new(2, 3, 4));
+  }
+
+  #[test]
+  fn distances() {
+    let a = Point2::new(-3i32, 4);
+    let b = Point2::new(2, -8);
+    assert_eq!(a.manhattan_dist(&b), 17u32);
+    assert_eq!(a.chebyshev_dist(&b), 12);
+    assert_eq!(Point2::new(0i32, 0).euclidean_dist(&Point2::new(3, 4)), 5.0);
+    let origin = Point4::<i8>::origin();
+    assert_eq!(origin.manhattan_dist(&Point4::new(1, -2, 3, -4)), 10u8);
+  }
+
+  #[test]
+  fn distances_do_not_overflow() {
+    let a = Point2::new(i32::MIN, 0);
+    let b = Point2::new(i32::MAX, 0);
+    assert_eq!(a.manhattan_dist(&b), u32::MAX);
+    let c = Point2::<u32>::new(0, 0);
+    assert_eq!(c.manhattan_dist(&Point2::new(u32::MAX, 0)), u32::MAX);
+
+    let (min, max) = (
+      Point2::new(i32::MIN, i32::MIN),
+      Point2::new(i32::MAX, i32::MAX),
+    );
+    assert_eq!(min.checked_manhattan_dist(&max), None);
+    assert_eq!(min.chebyshev_dist(&max), u32::MAX);
+    let corner = Point3::new(i32::MIN, 0, 0);
+    assert_eq!(
+      corner.checked_manhattan_dist(&Point3::new(0, i32::MAX, 0)),
+      Some(u32::MAX)
+    );
+    assert_eq!(
+      corner.checked_manhattan_dist(&Point3::new(0, i32::MAX, 1)),
+      None
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "manhattan distance overflows")]
+  fn manhattan_dist_panics_on_overflow() {
+    Point2::new(i32::MIN, i32::MIN).manhattan_dist(&Point2::new(i32::MAX, i32::MAX));
+  }
+
+  #[test]
+  fn rotation() {
+    let up = Dir4::Up.delta::<i32>();
+    assert_eq!(up.rotate_right(), Dir4::Right.delta());
+    assert_eq!(up.rotate_left(), Dir4::Left.delta());
+    assert_eq!(
+      Point2::new(2i64, 1).rotate_right().rotate_left(),
+      Point2::new(2, 1)
+    );
+  }
+
+  #[test]
+  fn directions() {
+    assert_eq!(Dir4::Left.turn_right(), Dir4::Up);
+    assert_eq!(Dir4::Up.turn_left(), Dir4::Left);
+    assert_eq!(Dir4::Down.reverse(), Dir4::Up);
+    assert_eq!(Dir8::Up.turn_left(), Dir8::UpLeft);
+    assert_eq!(Dir8::from(Dir4::Down), Dir8::Down);
+    assert_eq!(Dir8::DownLeft.delta::<i32>(), Point2::new(-1, 1));
+    let ring: Vec<Point2<i32>> = Dir8::ALL.iter().map(|d| d.delta()).collect();
+    assert!(ring
+      .iter()
+      .all(|p| p.chebyshev_dist(&Point2::origin()) == 1));
+  }
+}
@@ -0,0 +1,142 @@
+use crate::parse::ParseError;
+use std::marker::PhantomData;
+
+/// An integer type that `ints` can read.
+pub trait ScanInt: Copy + 'static {
+  const NAME: &'static str;
+  /// Reads ASCII `digits`, or `None` if the number doesn't fit.
+  fn from_digits(digits: &[u8], negative: bool) -> Option<Self>;
+}
+
+macro_rules! scan_int {
+  ($($t:ty),*) => {$(
+    impl ScanInt for $t {
+      const NAME: &'static str = stringify!($t);
+      fn from_digits(digits: &[u8], negative: bool) -> Option<$t> {
+        let mut n: $t = 0;
+        for &b in digits {
+          let d = (b - b'0') as $t;
+          n = n.checked_mul(10)?;
+          // build negative numbers downwards so MIN fits, and so unsigned
+          // types reject anything below zero
+          n = if negative { n.checked_sub(d)? } else { n.checked_add(d)? };
+        }
+        Some(n)
+      }
+    }
+  )*};
+}
+
+scan_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Iterator over the integers in a string, created by `ints`.
+pub struct Ints<'a, T> {
+  s: &'a str,
+  pos: usize,
+  _int: PhantomData<T>,
+}
+
+/// Finds every integer in `s`, skipping whatever lies between them, without
+/// allocating. A `-` or `+` directly before the digits is a sign unless it
+/// follows another digit, so `x=-3..5` gives -3 and 5 while `1518-11-01`
+/// gives 1518, 11 and 1. Numbers that don't fit in `T` are errors pointing
+/// at the number.
+pub fn ints<T: ScanInt>(s: &str) -> Ints<'_, T> {
+  Ints {
+    s,
+    pos: 0,
+    _int: PhantomData,
+  }
+}
+
+impl<'a, T: ScanInt> Ints<'a, T> {
+  /// Yields each number along with the byte offset it starts at, sign
+  /// included.
+  pub fn with_offsets(self) -> impl Iterator<Item = Result<(usize, T), ParseError>> + 'a {
+    let mut ints = self;
+    std::iter::from_fn(move || ints.next_with_offset())
+  }
+
+  fn next_with_offset(&mut self) -> Option<Result<(usize, T), ParseError>> {
+    let bytes = self.s.as_bytes();
+    let digits_start = self.pos + bytes[self.pos..].iter().position(u8::is_ascii_digit)?;
+    let digits_end = bytes[digits_start..]
+      .iter()
+      .position(|b| !b.is_ascii_digit())
+      .map_or(bytes.len(), |i| digits_start + i);
+    self.pos = digits_end;
+
+    let has_sign = digits_start > 0
+      && matches!(bytes[digits_start - 1], b'-' | b'+')
+      && (digits_start < 2 || !bytes[digits_start - 2].is_ascii_digit());
+    let start = if has_sign {
+      digits_start - 1
+    } else {
+      digits_start
+    };
+    let negative = has_sign && bytes[start] == b'-';
+    Some(
+      T::from_digits(&bytes[digits_start..digits_end], negative)
+        .map(|n| (start, n))
+        .ok_or_else(|| {
+          ParseError::new(format!(
+            "{} doesn't fit in {}",
+            &self.s[start..digits_end],
+            T::NAME
+          ))
+          .at_offset(self.s, start)
+        }),
+    )
+  }
+}
+
+impl<T: ScanInt> Iterator for Ints<'_, T> {
+  type Item = Result<T, ParseError>;
+
+  fn next(&mut self) -> Option<Result<T, ParseError>> {
+    self.next_with_offset().map(|r| r.map(|(_, n)| n))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn all<T: ScanInt>(s: &str) -> Result<Vec<T>, ParseError> {
+    ints(s).collect()
+  }
+
+  #[test]
+  fn signs() {
+    assert_eq!(all::<i32>("x=-3..5, y=+7"), Ok(vec![-3, 5, 7]));
+    assert_eq!(
+      all::<i32>("[1518-11-01 00:05]"),
+      Ok(vec![1518, 11, 1, 0, 5])
+    );
+    assert_eq!(all::<i64>("--2 -"), Ok(vec![-2]));
+  }
+
+  #[test]
+  fn wide_types() {
+    assert_eq!(all::<i8>("-128 127"), Ok(vec![i8::MIN, i8::MAX]));
+    assert_eq!(all::<u64>("18446744073709551615"), Ok(vec![u64::MAX]));
+    assert_eq!(
+      all::<i128>("-170141183460469231731687303715884105728"),
+      Ok(vec![i128::MIN])
+    );
+  }
+
+  #[test]
+  fn overflow() {
+    let err = all::<u8>("1, 256").unwrap_err();
+    assert_eq!(err.message, "256 doesn't fit in u8");
+    assert_eq!(err.column, 4);
+    assert_eq!(all::<u32>("a -1").unwrap_err().column, 3);
+  }
+
+  #[test]
+  fn offsets() {
+    let found: Result<Vec<(usize, i32)>, _> = ints("a 12 b -3").with_offsets().collect();
+    assert_eq!(found, Ok(vec![(2, 12), (7, -3)]));
+  }
+}
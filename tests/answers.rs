@@ -0,0 +1,38 @@
+use advent_of_code_2018_rust::answers::Answers;
+use advent_of_code_2018_rust::day3::Claim;
+use advent_of_code_2018_rust::input::{Loader, Source};
+use advent_of_code_2018_rust::solution::Registry;
+use std::path::Path;
+
+#[test]
+fn bundled_inputs_match_known_answers() {
+  let loader = Loader::new(Some(Path::new(env!("CARGO_MANIFEST_DIR")).join("data")));
+  for entry in Registry::new().iter() {
+    let source = Source::Day(entry.day);
+    let contents = loader.load(&source).unwrap();
+    let answers = Answers::load(&Answers::path_for(&loader.path(&source).unwrap()))
+      .unwrap()
+      .unwrap_or_default();
+    let input = entry.solution.parse(&contents).unwrap();
+    for part in 1..=2 {
+      if let Some(expected) = answers.expected(part) {
+        let actual = entry.solve(part, &*input);
+        assert_eq!(
+          actual.as_deref(),
+          Some(expected),
+          "day {} part {}",
+          entry.day,
+          part
+        );
+      }
+    }
+  }
+}
+
+#[test]
+fn domain_types_are_public() {
+  let a = Claim::new("#1 @ 1,3: 4x4").unwrap();
+  let b = Claim::new("#2 @ 3,1: 4x4").unwrap();
+  assert_eq!((a.id, a.xmax(), a.ymax()), (1, 5, 7));
+  assert!(a.overlaps(&b));
+}